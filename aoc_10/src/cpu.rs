@@ -0,0 +1,257 @@
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::{Observer, Program};
+
+/// Registers by name, `x` starting at 1 and the others at 0
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Registers(BTreeMap<char, i32>);
+
+impl Default for Registers {
+    fn default() -> Self {
+        Registers(BTreeMap::from([('x', 1)]))
+    }
+}
+
+impl Registers {
+    pub fn get(&self, name: char) -> i32 {
+        self.0.get(&name).copied().unwrap_or_default()
+    }
+
+    pub fn set(&mut self, name: char, value: i32) {
+        self.0.insert(name, value);
+    }
+
+    /// The register of the puzzle
+    pub fn x(&self) -> i32 {
+        self.get('x')
+    }
+}
+
+/// `x=1 y=-2`, the registers that were never set left out
+impl Display for Registers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let registers = self
+            .0
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<String>>();
+        write!(f, "{}", registers.join(" "))
+    }
+}
+
+/// Where to stop running
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Breakpoint {
+    /// Right before the cycle starts
+    Cycle(u32),
+    /// Right before the first cycle of the instruction at this index
+    Instruction(usize),
+}
+
+/// Why the CPU stopped running
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Stop {
+    Finished,
+    Breakpoint(Breakpoint),
+}
+
+/// Runs a program one cycle at a time, telling the observers what happens during every cycle
+pub struct Cpu {
+    program: Program,
+    registers: Registers,
+    /// The next cycle to run, from 1
+    cycle: u32,
+    /// Index of the instruction being run
+    pointer: usize,
+    /// Cycles of the current instruction already done
+    elapsed: u32,
+    observers: Vec<Box<dyn Observer>>,
+    breakpoints: Vec<Breakpoint>,
+    /// The cycle where the last `run` stopped, so that the next one doesn't stop there again
+    stopped_at: Option<u32>,
+}
+
+impl Cpu {
+    pub fn new(program: Program) -> Self {
+        Cpu {
+            program,
+            registers: Registers::default(),
+            cycle: 1,
+            pointer: 0,
+            elapsed: 0,
+            observers: vec![],
+            breakpoints: vec![],
+            stopped_at: None,
+        }
+    }
+
+    /// Add an observer, returning its index to get it back with `observer`
+    pub fn observe(&mut self, observer: Box<dyn Observer>) -> usize {
+        self.observers.push(observer);
+        self.observers.len() - 1
+    }
+
+    /// The observer at `index`, if it is a `T`
+    pub fn observer<T: Observer>(&self, index: usize) -> Option<&T> {
+        let observer: &dyn Any = self.observers.get(index)?.as_ref();
+        observer.downcast_ref()
+    }
+
+    /// Stop running, handing back the observers in the order they were added
+    pub fn into_observers(self) -> Vec<Box<dyn Observer>> {
+        self.observers
+    }
+
+    pub fn add_breakpoint(&mut self, breakpoint: Breakpoint) {
+        if !self.breakpoints.contains(&breakpoint) {
+            self.breakpoints.push(breakpoint);
+        }
+    }
+
+    pub fn remove_breakpoint(&mut self, breakpoint: Breakpoint) {
+        self.breakpoints.retain(|b| *b != breakpoint);
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    pub fn registers(&self) -> &Registers {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers {
+        &mut self.registers
+    }
+
+    /// The next cycle to run, from 1
+    pub fn cycle(&self) -> u32 {
+        self.cycle
+    }
+
+    /// Index of the instruction being run, the length of the program once it's done
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// Cycles of the current instruction already done
+    pub fn elapsed(&self) -> u32 {
+        self.elapsed
+    }
+
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pointer >= self.program.instructions.len()
+    }
+
+    /// Run one cycle. The observers see the registers as they are during the cycle, and the
+    /// instruction updates them at the end of its last cycle.
+    ///
+    /// Returns false if the program was already finished.
+    pub fn step(&mut self) -> bool {
+        let Some(instruction) = self.program.instructions.get(self.pointer) else {
+            return false;
+        };
+
+        for observer in &mut self.observers {
+            observer.during_cycle(self.cycle, &self.registers);
+        }
+
+        self.elapsed += 1;
+        if self.elapsed >= instruction.operation.cycles() {
+            instruction.operation.execute(&mut self.registers);
+            self.pointer += 1;
+            self.elapsed = 0;
+        }
+        self.cycle += 1;
+        true
+    }
+
+    /// The breakpoint stopping the CPU before the next cycle, if any
+    fn breakpoint(&self) -> Option<Breakpoint> {
+        self.breakpoints
+            .iter()
+            .find(|breakpoint| match breakpoint {
+                Breakpoint::Cycle(cycle) => *cycle == self.cycle,
+                Breakpoint::Instruction(index) => *index == self.pointer && self.elapsed == 0,
+            })
+            .copied()
+    }
+
+    /// Run until the end of the program or a breakpoint
+    pub fn run(&mut self) -> Stop {
+        loop {
+            if self.stopped_at != Some(self.cycle) {
+                if let Some(breakpoint) = self.breakpoint() {
+                    self.stopped_at = Some(self.cycle);
+                    return Stop::Breakpoint(breakpoint);
+                }
+            }
+            if !self.step() {
+                return Stop::Finished;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cpu::{Breakpoint, Cpu, Stop};
+    use crate::{Crt, InstructionSet, Observer, Registers};
+
+    /// Remembers `x` during every cycle
+    #[derive(Default)]
+    struct Trace(Vec<(u32, i32)>);
+
+    impl Observer for Trace {
+        fn during_cycle(&mut self, cycle: u32, registers: &Registers) {
+            self.0.push((cycle, registers.x()));
+        }
+    }
+
+    #[test]
+    pub fn test_cycles() {
+        let program = InstructionSet::default()
+            .decode_program("noop\naddx 3\naddx -5")
+            .unwrap();
+        let mut cpu = Cpu::new(program);
+        let trace = cpu.observe(Box::new(Trace::default()));
+        assert_eq!(cpu.run(), Stop::Finished);
+        assert_eq!(cpu.registers().x(), -1);
+        assert_eq!(cpu.cycle(), 6);
+        assert!(!cpu.step());
+
+        // `x` only changes after the two cycles of an `addx`
+        let cycles = &cpu.observer::<Trace>(trace).unwrap().0;
+        assert_eq!(cycles, &vec![(1, 1), (2, 1), (3, 1), (4, 4), (5, 4)]);
+        assert!(cpu.observer::<Crt>(trace).is_none());
+        assert!(cpu.observer::<Trace>(1).is_none());
+        assert_eq!(cpu.into_observers().len(), 1);
+    }
+
+    #[test]
+    pub fn test_breakpoints() {
+        let program = InstructionSet::default()
+            .decode_program("noop\naddx 3\naddy -5\nnoop")
+            .unwrap();
+        let mut cpu = Cpu::new(program);
+        cpu.add_breakpoint(Breakpoint::Cycle(3));
+        cpu.add_breakpoint(Breakpoint::Instruction(2));
+
+        // In the middle of the `addx`
+        assert_eq!(cpu.run(), Stop::Breakpoint(Breakpoint::Cycle(3)));
+        assert_eq!((cpu.pointer(), cpu.registers().x()), (1, 1));
+        assert_eq!(cpu.run(), Stop::Breakpoint(Breakpoint::Instruction(2)));
+        assert_eq!(cpu.cycle(), 4);
+        assert_eq!(cpu.registers().to_string(), "x=4");
+
+        cpu.remove_breakpoint(Breakpoint::Cycle(3));
+        assert_eq!(cpu.run(), Stop::Finished);
+        assert_eq!(cpu.registers().to_string(), "x=4 y=-5");
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+use anyhow::{bail, Context};
+
+use crate::Registers;
+
+/// What an instruction does, once decoded
+pub trait Operation: Debug {
+    /// Number of cycles the instruction takes, at least 1
+    fn cycles(&self) -> u32;
+
+    /// Update the registers, at the end of the last cycle
+    fn execute(&self, registers: &mut Registers);
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Noop;
+
+impl Operation for Noop {
+    fn cycles(&self) -> u32 {
+        1
+    }
+
+    fn execute(&self, _: &mut Registers) {}
+}
+
+/// Add a value to a register, `addx V` in the puzzle
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Add {
+    pub register: char,
+    pub value: i32,
+}
+
+impl Operation for Add {
+    fn cycles(&self) -> u32 {
+        2
+    }
+
+    fn execute(&self, registers: &mut Registers) {
+        registers.set(self.register, registers.get(self.register) + self.value);
+    }
+}
+
+/// Turns the name and the arguments of an instruction into its operation
+pub type Decoder = fn(&str, &[&str]) -> anyhow::Result<Box<dyn Operation>>;
+
+/// The instructions the CPU knows, by name
+#[derive(Debug, Clone)]
+pub struct InstructionSet {
+    decoders: HashMap<String, Decoder>,
+}
+
+/// `noop`, and `add` followed by the name of any register, like `addx`
+impl Default for InstructionSet {
+    fn default() -> Self {
+        let mut set = InstructionSet {
+            decoders: HashMap::new(),
+        };
+        set.insert("noop", |_, args| {
+            expect_args(args, 0)?;
+            Ok(Box::new(Noop))
+        });
+        for register in 'a'..='z' {
+            set.insert(&format!("add{register}"), |name, args| {
+                expect_args(args, 1)?;
+                Ok(Box::new(Add {
+                    register: name.chars().last().unwrap_or('x'),
+                    value: parse_value(args[0])?,
+                }))
+            });
+        }
+        set
+    }
+}
+
+impl InstructionSet {
+    /// Add an instruction, or replace an existing one
+    pub fn insert(&mut self, name: &str, decoder: Decoder) {
+        self.decoders.insert(name.to_owned(), decoder);
+    }
+
+    pub fn decode(&self, line: &str) -> anyhow::Result<Instruction> {
+        let mut words = line.split_whitespace();
+        let name = words.next().context("Empty instruction")?;
+        let args = words.collect::<Vec<&str>>();
+        let decoder = self
+            .decoders
+            .get(name)
+            .with_context(|| format!("Unknown instruction {name:?}"))?;
+        let operation = decoder(name, &args)?;
+        if operation.cycles() == 0 {
+            bail!("{name:?} takes no cycle");
+        }
+        Ok(Instruction {
+            text: line.trim().to_owned(),
+            operation,
+        })
+    }
+
+    /// Decode a whole program, one instruction per line
+    pub fn decode_program(&self, source: &str) -> anyhow::Result<Program> {
+        source
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                self.decode(line)
+                    .with_context(|| format!("Line {}: {line:?}", number + 1))
+            })
+            .collect::<anyhow::Result<Vec<Instruction>>>()
+            .map(|instructions| Program { instructions })
+    }
+}
+
+/// Check the number of arguments, for decoders
+pub fn expect_args(args: &[&str], count: usize) -> anyhow::Result<()> {
+    if args.len() != count {
+        bail!("Expected {count} arguments, found {}", args.len());
+    }
+    Ok(())
+}
+
+/// Parse a number argument, for decoders
+pub fn parse_value(arg: &str) -> anyhow::Result<i32> {
+    arg.parse()
+        .with_context(|| format!("{arg:?} is not a number"))
+}
+
+#[derive(Debug)]
+pub struct Instruction {
+    /// The line the instruction comes from
+    pub text: String,
+    pub operation: Box<dyn Operation>,
+}
+
+/// A decoded program
+#[derive(Debug, Default)]
+pub struct Program {
+    pub instructions: Vec<Instruction>,
+}
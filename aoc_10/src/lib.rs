@@ -1,79 +1,47 @@
-use std::collections::HashMap;
-use std::fmt;
+use anyhow::Context;
 
-#[derive(Debug)]
-enum Instruction {
-    Noop,
-    Addx(i32),
-}
+pub use crate::cpu::{Breakpoint, Cpu, Registers, Stop};
+pub use crate::instruction::{
+    expect_args, parse_value, Add, Decoder, Instruction, InstructionSet, Noop, Operation, Program,
+};
+pub use crate::observer::{Crt, Observer, SignalStrength};
+pub use crate::ocr::read_letters;
 
-fn print_pixel<T>(register: &i32, cycles: &i32, output: &mut T)
-where
-    T: fmt::Write,
-{
-    let tick = (cycles - 1) % 40;
-    if (register - 1..=register + 1).contains(&tick) {
-        write!(output, "#").unwrap();
-    } else {
-        write!(output, " ").unwrap();
-    }
-
-    if tick == 39 {
-        writeln!(output).unwrap();
-    }
-}
+mod cpu;
+mod instruction;
+mod observer;
+mod ocr;
 
 pub fn solution(input_path: &str) -> Option<(i32, String)> {
-    let mut output = String::default();
-    let mut register = 1;
-    let mut cycles = 1;
-    let marks = vec![20, 60, 100, 140, 180, 220];
-    let mut positions = HashMap::<i32, i32>::new();
-
-    std::fs::read_to_string(input_path)
-        .ok()?
-        .lines()
-        .map(parse_instruction)
-        .for_each(|instruction| {
-            print_pixel(&register, &cycles, &mut output);
-
-            cycles += 1;
-            positions.insert(cycles, register);
+    let program = read_program(input_path).ok()?;
+    let mut cpu = Cpu::new(program);
+    let signal = cpu.observe(Box::new(SignalStrength::default()));
+    let crt = cpu.observe(Box::new(Crt::default()));
+    cpu.run();
 
-            match instruction {
-                Instruction::Noop => (), // Nope.
-                Instruction::Addx(v) => {
-                    print_pixel(&register, &cycles, &mut output);
-
-                    cycles += 1;
-                    register += v;
-                    positions.insert(cycles, register);
-                }
-            };
-        });
+    Some((
+        cpu.observer::<SignalStrength>(signal)?.sum,
+        cpu.observer::<Crt>(crt)?.output.clone(),
+    ))
+}
 
-    let sum = positions
-        .iter()
-        .filter(|(k, _)| marks.contains(*k))
-        .map(|(k, v)| *k as i32 * v)
-        .sum();
-    Some((sum, output))
+/// The letters shown on the screen once the program is done
+pub fn part2(input_path: &str) -> anyhow::Result<String> {
+    let (_, output) =
+        solution(input_path).with_context(|| format!("Can't run the program in {input_path}"))?;
+    read_letters(&output)
 }
 
-fn parse_instruction(line: &str) -> Instruction {
-    match line {
-        l if l.starts_with("addx") => {
-            let value = l.split_once(' ').unwrap().1.parse::<i32>().unwrap();
-            Instruction::Addx(value)
-        }
-        l if l.starts_with("noop") => Instruction::Noop,
-        _ => unreachable!(),
-    }
+/// Read and decode a program with the instructions of the puzzle
+pub fn read_program(input_path: &str) -> anyhow::Result<Program> {
+    let source =
+        std::fs::read_to_string(input_path).with_context(|| format!("Can't read {input_path}"))?;
+    InstructionSet::default().decode_program(&source)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::solution;
+    use crate::{expect_args, solution, Cpu, InstructionSet, Operation, Registers};
 
     #[test]
     pub fn test_solution() {
@@ -84,4 +52,42 @@ mod tests {
             std::fs::read_to_string("expected_output.txt").unwrap()
         );
     }
+
+    /// Swap `x` and `y`, in 3 cycles
+    #[derive(Debug)]
+    struct Swap;
+
+    impl Operation for Swap {
+        fn cycles(&self) -> u32 {
+            3
+        }
+
+        fn execute(&self, registers: &mut Registers) {
+            let (x, y) = (registers.get('x'), registers.get('y'));
+            registers.set('x', y);
+            registers.set('y', x);
+        }
+    }
+
+    #[test]
+    pub fn test_new_opcode() {
+        let mut set = InstructionSet::default();
+        set.insert("swap", |_, args| {
+            expect_args(args, 0)?;
+            Ok(Box::new(Swap))
+        });
+
+        let mut cpu = Cpu::new(set.decode_program("addy 7\nswap\nnoop").unwrap());
+        cpu.run();
+        assert_eq!(cpu.cycle(), 7);
+        assert_eq!(cpu.registers().to_string(), "x=7 y=1");
+
+        let error = set.decode_program("noop\nswap 1").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 2: \"swap 1\": Expected 0 arguments, found 1"
+        );
+        let error = InstructionSet::default().decode("swap").unwrap_err();
+        assert_eq!(error.to_string(), "Unknown instruction \"swap\"");
+    }
 }
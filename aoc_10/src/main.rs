@@ -1,8 +1,140 @@
-use aoc_10::solution;
+use std::io::{BufRead, Write};
+
+use anyhow::{bail, Context};
+use aoc_10::{part2, read_program, solution, Breakpoint, Cpu, Stop};
 
 fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("debug") {
+        let input = args.get(2).map_or("input.txt", String::as_str);
+        if let Err(e) = debug(input) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
     if let Some((x, output)) = solution("input.txt") {
         println!("{output}");
         println!("Part 1: {x}");
     }
+
+    match part2("input.txt") {
+        Ok(letters) => println!("Part 2: {letters}"),
+        Err(e) => eprintln!("{e:#}"),
+    }
+}
+
+const HELP: &str = "\
+step [N]                     run N cycles, 1 by default, ignoring the breakpoints
+continue                     run until a breakpoint or the end of the program
+break cycle|instruction N    stop before a cycle, or the first cycle of an instruction
+delete cycle|instruction N   remove a breakpoint
+breakpoints                  list the breakpoints
+set R V                      set the register R to V
+state                        print the registers and the current instruction
+quit";
+
+/// Read commands from the standard input, and run the program as told
+fn debug(input: &str) -> anyhow::Result<()> {
+    let mut cpu = Cpu::new(read_program(input)?);
+    println!("{HELP}");
+    print_state(&cpu);
+
+    let stdin = std::io::stdin();
+    loop {
+        print!("> ");
+        std::io::stdout().flush()?;
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line)? == 0 {
+            return Ok(());
+        }
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        match words.as_slice() {
+            [] => continue,
+            ["quit" | "q"] => return Ok(()),
+            words => {
+                if let Err(e) = command(&mut cpu, words) {
+                    println!("{e:#}");
+                }
+            }
+        }
+    }
+}
+
+fn command(cpu: &mut Cpu, words: &[&str]) -> anyhow::Result<()> {
+    match words {
+        ["step" | "s", rest @ ..] => {
+            let count = match rest {
+                [] => 1,
+                [count] => count
+                    .parse()
+                    .with_context(|| format!("Bad count {count:?}"))?,
+                _ => bail!("Usage: step [N]"),
+            };
+            for _ in 0..count {
+                if !cpu.step() {
+                    break;
+                }
+            }
+            print_state(cpu);
+        }
+        ["continue" | "c"] => {
+            match cpu.run() {
+                Stop::Finished => println!("Finished"),
+                Stop::Breakpoint(breakpoint) => println!("Stopped at {breakpoint:?}"),
+            }
+            print_state(cpu);
+        }
+        ["break" | "b", kind, value] => cpu.add_breakpoint(breakpoint(kind, value)?),
+        ["delete" | "d", kind, value] => cpu.remove_breakpoint(breakpoint(kind, value)?),
+        ["breakpoints"] => {
+            for breakpoint in cpu.breakpoints() {
+                println!("{breakpoint:?}");
+            }
+        }
+        ["set", register, value] => {
+            let mut chars = register.chars();
+            let (Some(name), None) = (chars.next(), chars.next()) else {
+                bail!("Registers have one letter names");
+            };
+            let value = value
+                .parse()
+                .with_context(|| format!("Bad value {value:?}"))?;
+            cpu.registers_mut().set(name, value);
+            print_state(cpu);
+        }
+        ["state" | "regs" | "r"] => print_state(cpu),
+        _ => bail!("Unknown command\n{HELP}"),
+    }
+    Ok(())
+}
+
+fn breakpoint(kind: &str, value: &str) -> anyhow::Result<Breakpoint> {
+    let value = value
+        .parse()
+        .with_context(|| format!("Bad number {value:?}"))?;
+    match kind {
+        "cycle" => Ok(Breakpoint::Cycle(value)),
+        "instruction" | "instr" => Ok(Breakpoint::Instruction(value as usize)),
+        _ => bail!("Breakpoints are on a cycle or an instruction, not {kind:?}"),
+    }
+}
+
+fn print_state(cpu: &Cpu) {
+    match cpu.program().instructions.get(cpu.pointer()) {
+        Some(instruction) => println!(
+            "Cycle {}, instruction {}: {} ({} of {} cycles done), {}",
+            cpu.cycle(),
+            cpu.pointer(),
+            instruction.text,
+            cpu.elapsed(),
+            instruction.operation.cycles(),
+            cpu.registers()
+        ),
+        None => println!(
+            "Cycle {}, program finished, {}",
+            cpu.cycle(),
+            cpu.registers()
+        ),
+    }
 }
@@ -0,0 +1,117 @@
+use std::any::Any;
+
+use anyhow::bail;
+
+use crate::Registers;
+
+/// Something watching the CPU run. Observers are `Any` so that the CPU can hand them back, see
+/// `Cpu::observer`.
+pub trait Observer: Any {
+    /// Called at the start of every cycle, with the registers as they are during it
+    fn during_cycle(&mut self, cycle: u32, registers: &Registers);
+}
+
+/// Sum of the signal strengths, the cycle number times `x`, during some cycles
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SignalStrength {
+    pub marks: Vec<u32>,
+    pub sum: i32,
+}
+
+/// The cycles of the puzzle, 20 and every 40 cycles after that up to 220
+impl Default for SignalStrength {
+    fn default() -> Self {
+        SignalStrength {
+            marks: (20..=220).step_by(40).collect(),
+            sum: 0,
+        }
+    }
+}
+
+impl Observer for SignalStrength {
+    fn during_cycle(&mut self, cycle: u32, registers: &Registers) {
+        if self.marks.contains(&cycle) {
+            self.sum += cycle as i32 * registers.x();
+        }
+    }
+}
+
+/// The screen, drawing one pixel per cycle, lit when the 3 pixels wide sprite centered on `x` is
+/// over it
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Crt {
+    /// Never 0, see `new`
+    width: u32,
+    pub lit: char,
+    pub dark: char,
+    /// Rows drawn so far, each ending with a new line once complete
+    pub output: String,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Crt {
+            width: 40,
+            lit: '#',
+            dark: ' ',
+            output: String::new(),
+        }
+    }
+}
+
+impl Crt {
+    /// A screen of the puzzle, but `width` pixels wide
+    pub fn new(width: u32) -> anyhow::Result<Self> {
+        if width == 0 {
+            bail!("The screen must be at least a pixel wide");
+        }
+        Ok(Crt {
+            width,
+            ..Crt::default()
+        })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+}
+
+impl Observer for Crt {
+    fn during_cycle(&mut self, cycle: u32, registers: &Registers) {
+        // Cycles start at 1, there is nothing to draw before
+        let Some(pixel) = cycle.checked_sub(1) else {
+            return;
+        };
+        let tick = (pixel % self.width) as i32;
+        let x = registers.x();
+        self.output.push(if (x - 1..=x + 1).contains(&tick) {
+            self.lit
+        } else {
+            self.dark
+        });
+
+        if tick == self.width as i32 - 1 {
+            self.output.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::observer::{Crt, Observer};
+    use crate::Registers;
+
+    #[test]
+    pub fn test_crt() {
+        assert!(Crt::new(0).is_err());
+
+        let mut crt = Crt::new(3).unwrap();
+        assert_eq!(crt.width(), 3);
+        crt.during_cycle(0, &Registers::default());
+        for cycle in 1..=4 {
+            crt.during_cycle(cycle, &Registers::default());
+        }
+        // The sprite covers 0 to 2 around `x` = 1
+        assert_eq!(crt.output, "###\n#");
+    }
+}
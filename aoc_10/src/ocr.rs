@@ -0,0 +1,96 @@
+use anyhow::bail;
+
+/// Height of the letters, and of the screen
+pub const GLYPH_HEIGHT: usize = 6;
+/// Width of a letter, followed by an empty column
+pub const GLYPH_WIDTH: usize = 4;
+
+/// The letters known to show up on the screen, row by row
+const FONT: [(char, [&str; GLYPH_HEIGHT]); 16] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+/// Read the capital letters drawn on the screen, `#` being lit and anything else dark.
+///
+/// Every letter takes 5 columns, the last one empty. The error lists every glyph that isn't a
+/// known letter.
+pub fn read_letters(image: &str) -> anyhow::Result<String> {
+    let rows = image
+        .lines()
+        .map(|row| row.chars().map(|c| c == '#').collect::<Vec<bool>>())
+        .collect::<Vec<Vec<bool>>>();
+    if rows.len() != GLYPH_HEIGHT {
+        bail!("Expected {GLYPH_HEIGHT} rows, found {}", rows.len());
+    }
+    let width = rows.iter().map(Vec::len).max().unwrap_or_default();
+
+    let mut letters = String::new();
+    let mut unknown = vec![];
+    for (index, left) in (0..width).step_by(GLYPH_WIDTH + 1).enumerate() {
+        let lit = |row: &Vec<bool>, x: usize| row.get(x).copied().unwrap_or_default();
+        let glyph = rows
+            .iter()
+            .map(|row| {
+                (left..left + GLYPH_WIDTH)
+                    .map(|x| if lit(row, x) { '#' } else { '.' })
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>();
+        let spaced = rows.iter().all(|row| !lit(row, left + GLYPH_WIDTH));
+
+        match FONT.iter().find(|(_, font)| *font == glyph.as_slice()) {
+            Some((letter, _)) if spaced => letters.push(*letter),
+            _ => unknown.push(format!("Glyph {}:\n{}", index + 1, glyph.join("\n"))),
+        }
+    }
+
+    if !unknown.is_empty() {
+        bail!("Unrecognised glyphs\n{}", unknown.join("\n"));
+    }
+    Ok(letters)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ocr::{read_letters, FONT};
+
+    #[test]
+    pub fn test_read_letters() {
+        // Every letter of the font, with the dark pixels as spaces like on the screen
+        let image = (0..6)
+            .map(|y| {
+                FONT.iter()
+                    .map(|(_, glyph)| format!("{} ", glyph[y].replace('.', " ")))
+                    .collect::<String>()
+            })
+            .collect::<Vec<String>>()
+            .join("\n");
+        assert_eq!(read_letters(&image).unwrap(), "ABCEFGHJKLOPRSUZ");
+
+        let error = read_letters(&std::fs::read_to_string("expected_output.txt").unwrap())
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("Unrecognised glyphs\nGlyph 1:\n##..\n###.\n####\n"));
+        assert!(error.contains("Glyph 8:"));
+
+        assert_eq!(
+            read_letters("####").unwrap_err().to_string(),
+            "Expected 6 rows, found 1"
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 use std::ops::RangeInclusive;
 
+use interval_set::IntervalSet;
 use rayon::iter::IntoParallelRefIterator;
 use rayon::prelude::*;
 
@@ -54,30 +55,18 @@ pub fn part1(input_path: &str, y_to_scan: isize) -> Option<usize> {
         .filter(|b| b.1 == y_to_scan)
         .collect::<HashSet<Position>>();
 
-    // Find all the ranges covered by the sensors on y
-    let ranges = input
-        .par_iter()
+    // Merge all the ranges covered by the sensors on y
+    let coverage = input
+        .iter()
         .filter_map(|(s, _)| s.get_x_range_for_y(&y_to_scan))
-        .collect::<Vec<RangeInclusive<isize>>>();
-
-    // Min and Max of the x covered by the sensors
-    let min_x = *ranges.iter().map(RangeInclusive::start).min().unwrap();
-    let max_x = *ranges.iter().map(RangeInclusive::end).max().unwrap();
-
-    // Return each position scanned by any sensors.
-    // Note: it would more efficient to build a global coverage range.
-    let scanned_positions = (min_x..=max_x)
-        .into_par_iter()
-        .filter_map(|x| {
-            let position = Position(x, y_to_scan);
-            ranges
-                .par_iter()
-                .find_map_any(|r| if r.contains(&x) { Some(position) } else { None })
-        })
-        .collect::<Vec<Position>>();
+        .collect::<IntervalSet<isize>>();
 
     // Number of scanned position, less the kevin beacons
-    Some(scanned_positions.len() - beacons_position_on_y.len())
+    let beacons_in_coverage = beacons_position_on_y
+        .iter()
+        .filter(|b| coverage.contains(&b.0))
+        .count();
+    Some(coverage.covered_len() as usize - beacons_in_coverage)
 }
 
 pub fn part2(input_path: &str, max_size: isize) -> Option<isize> {
@@ -95,40 +84,21 @@ pub fn part2(input_path: &str, max_size: isize) -> Option<isize> {
 /// Find the "hole" in the sensor coverage
 fn find_the_spot(sensors: &[Sensor], max_size: isize) -> Option<Position> {
     (0..=max_size).into_par_iter().find_map_any(|y| {
-        // Build the range covered by the sensors on the y line
-        let mut ranges = sensors
-            .par_iter()
+        // Build the coverage of the sensors on the y line
+        let coverage = sensors
+            .iter()
             .flat_map(|x| x.get_x_range_for_y(&y))
-            .collect::<Vec<RangeInclusive<isize>>>();
-
-        // Search for hole in all those ranges
-        search_for_hole_in_ranges(&mut ranges, max_size).map(|x| Position(x, y))
+            .collect::<IntervalSet<isize>>();
+
+        // Anything not covered between 0 and `max_size` is our hole
+        coverage
+            .complement(0..=max_size)
+            .ranges()
+            .next()
+            .map(|x| Position(*x.start(), y))
     })
 }
 
-/// Takes a slice of ranges, and check for "holes" in it (ie value not covered by any of the ranges)
-/// , stopping to the first one we find. Limits the range to scan from 0 to `max_width` included.
-/// Not my proudest code, but does the job
-fn search_for_hole_in_ranges(
-    ranges: &mut [RangeInclusive<isize>],
-    max_width: isize,
-) -> Option<isize> {
-    ranges.sort_by(|a, b| a.start().cmp(b.start())); // Sort the ranges
-
-    let mut range_coverage = &0isize..=ranges[0].end().max(&1); // Initial range, from 0 to as least 1.
-    for r in &ranges[1..] {
-        if *range_coverage.end() + 1 < *r.start() {
-            // If the end of the global range ends before the begin of the next range, there it is, we have our hole.
-            return Some(*range_coverage.end() + 1);
-        } else {
-            // "Extends the range
-            range_coverage =
-                *range_coverage.start()..=r.end().clamp(range_coverage.end(), &max_width);
-        }
-    }
-    None
-}
-
 /// Parse a line of the input to return a tuple of Set/Beacon.
 /// This is so ugly, please forgive me.
 fn parse_input(line: &str) -> (Sensor, Beacon) {
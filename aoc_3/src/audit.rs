@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use anyhow::{bail, Context};
+
+use crate::{score, split_compartments, ItemSet};
+
+/// An item type found in both compartments of a rucksack
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SharedItem {
+    pub item: char,
+    pub priority: u32,
+    /// Number of times the item appears in the first compartment
+    pub first_count: usize,
+    /// Number of times the item appears in the second compartment
+    pub second_count: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RucksackAudit {
+    /// Line of the rucksack in the input, starting at 1
+    pub line: usize,
+    pub shared: Vec<SharedItem>,
+}
+
+impl RucksackAudit {
+    /// A well packed rucksack has exactly one item type in both compartments
+    pub fn is_anomaly(&self) -> bool {
+        self.shared.len() != 1
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct GroupAudit {
+    /// Line of the first rucksack of the group, starting at 1
+    pub first_line: usize,
+    pub size: usize,
+    /// Item types carried by every elf of the group
+    pub duplicates: ItemSet,
+}
+
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct ItemStats {
+    /// Number of rucksacks where the item is in both compartments
+    pub misplaced_in: usize,
+    /// Number of groups where every elf carries the item
+    pub common_in_groups: usize,
+    /// Total number of occurrences of the item, all rucksacks included
+    pub total_count: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Audit {
+    pub rucksacks: Vec<RucksackAudit>,
+    pub groups: Vec<GroupAudit>,
+    pub stats: BTreeMap<char, ItemStats>,
+}
+
+impl Audit {
+    /// Rucksacks with no shared item type, or more than one
+    pub fn anomalies(&self) -> impl Iterator<Item = &RucksackAudit> {
+        self.rucksacks.iter().filter(|r| r.is_anomaly())
+    }
+
+    /// Sum of the priorities of every shared item type of every rucksack
+    pub fn priority_sum(&self) -> u32 {
+        self.rucksacks
+            .iter()
+            .flat_map(|r| &r.shared)
+            .map(|s| s.priority)
+            .sum()
+    }
+}
+
+/// Audit every rucksack of the input, and every group of `group_size` rucksacks.
+///
+/// Unlike `part1` and `part2`, a rucksack without a single shared item is not an error: it is
+/// reported as an anomaly. The last group may be smaller if the rucksacks can't be split evenly.
+pub fn audit(input_path: &str, group_size: usize) -> anyhow::Result<Audit> {
+    if group_size == 0 {
+        bail!("Group size must be at least 1");
+    }
+    let input = std::fs::read_to_string(input_path)?;
+    let lines = input.lines().collect::<Vec<&str>>();
+    let mut stats = BTreeMap::<char, ItemStats>::new();
+
+    let mut rucksacks = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let rucksack = audit_rucksack(line).with_context(|| format!("Line {}", index + 1))?;
+        for item in line.chars() {
+            stats.entry(item).or_default().total_count += 1;
+        }
+        for shared in &rucksack {
+            stats.entry(shared.item).or_default().misplaced_in += 1;
+        }
+        rucksacks.push(RucksackAudit {
+            line: index + 1,
+            shared: rucksack,
+        });
+    }
+
+    let mut groups = Vec::with_capacity(lines.len() / group_size + 1);
+    for (index, group) in lines.chunks(group_size).enumerate() {
+        let mut duplicates = group[0].parse::<ItemSet>()?;
+        for rucksack in &group[1..] {
+            duplicates = duplicates.intersection(&rucksack.parse()?);
+        }
+        for item in duplicates.items() {
+            stats.entry(item).or_default().common_in_groups += 1;
+        }
+        groups.push(GroupAudit {
+            first_line: index * group_size + 1,
+            size: group.len(),
+            duplicates,
+        });
+    }
+
+    Ok(Audit {
+        rucksacks,
+        groups,
+        stats,
+    })
+}
+
+/// List every item type present in both compartments, with their count in each one
+fn audit_rucksack(rucksack: &str) -> anyhow::Result<Vec<SharedItem>> {
+    let (first, second) = split_compartments(rucksack)?;
+    let first_counts = count_items(first)?;
+    let second_counts = count_items(second)?;
+
+    let mut shared = vec![];
+    for item in first
+        .parse::<ItemSet>()?
+        .intersection(&second.parse()?)
+        .items()
+    {
+        let priority = score(&item)?;
+        shared.push(SharedItem {
+            item,
+            priority,
+            first_count: first_counts[priority as usize],
+            second_count: second_counts[priority as usize],
+        });
+    }
+    Ok(shared)
+}
+
+/// Count the items of a compartment, indexed by priority
+fn count_items(compartment: &str) -> anyhow::Result<[usize; 53]> {
+    let mut counts = [0; 53];
+    for item in compartment.chars() {
+        counts[score(&item)? as usize] += 1;
+    }
+    Ok(counts)
+}
+
+impl fmt::Display for Audit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Rucksacks:")?;
+        for rucksack in &self.rucksacks {
+            let shared = rucksack
+                .shared
+                .iter()
+                .map(|s| format!("{} ({}/{})", s.item, s.first_count, s.second_count))
+                .collect::<Vec<String>>()
+                .join(", ");
+            let flag = if rucksack.is_anomaly() {
+                " <- anomaly"
+            } else {
+                ""
+            };
+            writeln!(f, "  {:>4}: {shared}{flag}", rucksack.line)?;
+        }
+
+        writeln!(f, "Groups:")?;
+        for group in &self.groups {
+            writeln!(
+                f,
+                "  {:>4}..{:<4}: {}",
+                group.first_line,
+                group.first_line + group.size - 1,
+                group.duplicates
+            )?;
+        }
+
+        writeln!(f, "Items:")?;
+        for (item, stats) in &self.stats {
+            writeln!(
+                f,
+                "  {item}: misplaced in {}, common in {} groups, {} total",
+                stats.misplaced_in, stats.common_in_groups, stats.total_count
+            )?;
+        }
+
+        write!(
+            f,
+            "{} anomalies, priority sum {}",
+            self.anomalies().count(),
+            self.priority_sum()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::audit;
+    use crate::audit::audit_rucksack;
+
+    #[test]
+    pub fn test_audit() {
+        let audit = audit("input_test.txt", 3).unwrap();
+
+        assert_eq!(audit.rucksacks.len(), 6);
+        assert_eq!(audit.anomalies().count(), 0);
+        assert_eq!(audit.priority_sum(), 157);
+        assert_eq!(audit.groups[0].duplicates.to_string(), "r");
+        assert_eq!(audit.groups[1].duplicates.to_string(), "Z");
+        assert_eq!(audit.stats[&'p'].misplaced_in, 1);
+        assert_eq!(audit.stats[&'r'].common_in_groups, 1);
+    }
+
+    #[test]
+    pub fn test_audit_rucksack() {
+        let shared = audit_rucksack("aabXcaXX").unwrap();
+        assert_eq!(shared.len(), 2);
+        assert_eq!(
+            (
+                shared[0].item,
+                shared[0].first_count,
+                shared[0].second_count
+            ),
+            ('a', 2, 1)
+        );
+        assert_eq!(
+            (
+                shared[1].item,
+                shared[1].first_count,
+                shared[1].second_count
+            ),
+            ('X', 1, 2)
+        );
+
+        assert!(audit_rucksack("abcd").unwrap().is_empty());
+        assert!(audit_rucksack("abc").is_err());
+        assert!(audit_rucksack("aéb").is_err());
+    }
+}
@@ -0,0 +1,163 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+use crate::score;
+
+/// A set of item types, stored as a bitmask.
+///
+/// Each item type uses the bit matching its priority, so `a` is bit 1 and `Z` is bit 52. Set
+/// operations are then a single `&` or `|`, no allocation needed.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ItemSet(u64);
+
+impl ItemSet {
+    pub fn new() -> Self {
+        ItemSet(0)
+    }
+
+    /// Add an item to the set. Fails if the item is not an ASCII letter.
+    pub fn insert(&mut self, item: char) -> anyhow::Result<()> {
+        self.0 |= 1 << bit_index(item)?;
+        Ok(())
+    }
+
+    pub fn contains(&self, item: char) -> bool {
+        bit_index(item).is_ok_and(|bit| self.0 & (1 << bit) != 0)
+    }
+
+    pub fn intersection(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 & other.0)
+    }
+
+    pub fn union(&self, other: &ItemSet) -> ItemSet {
+        ItemSet(self.0 | other.0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the items of the set, by increasing priority
+    pub fn items(&self) -> impl Iterator<Item = char> + '_ {
+        (1..=52u32)
+            .filter(|bit| self.0 & (1 << bit) != 0)
+            .map(item_from_bit)
+    }
+
+    /// Return the single item of the set, failing if there is none or more than one
+    pub fn single(&self) -> anyhow::Result<char> {
+        match self.len() {
+            1 => Ok(item_from_bit(self.0.trailing_zeros())),
+            0 => bail!("No common item found"),
+            _ => bail!(
+                "Several common items found: {}",
+                self.items().collect::<String>()
+            ),
+        }
+    }
+}
+
+impl FromStr for ItemSet {
+    type Err = anyhow::Error;
+
+    fn from_str(items: &str) -> Result<Self, Self::Err> {
+        let mut set = ItemSet::new();
+        for item in items.chars() {
+            set.insert(item)?;
+        }
+        Ok(set)
+    }
+}
+
+impl fmt::Display for ItemSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.items().collect::<String>())
+    }
+}
+
+/// Bit used for an item type, which is simply its priority
+fn bit_index(item: char) -> anyhow::Result<u32> {
+    score(&item)
+}
+
+fn item_from_bit(bit: u32) -> char {
+    if bit <= 26 {
+        char::from(b'a' + (bit - 1) as u8)
+    } else {
+        char::from(b'A' + (bit - 27) as u8)
+    }
+}
+
+/// Split a rucksack in its two compartments, failing if they can't hold as many items each
+pub fn split_compartments(rucksack: &str) -> anyhow::Result<(&str, &str)> {
+    // Items are ASCII letters, so the middle byte is the middle item
+    if !rucksack.is_ascii() {
+        bail!("Rucksack {rucksack:?} has items that are not letters");
+    }
+    if !rucksack.len().is_multiple_of(2) {
+        bail!("Rucksack {rucksack:?} has an odd number of items");
+    }
+    Ok(rucksack.split_at(rucksack.len() / 2))
+}
+
+/// Find the item present in both compartments of a rucksack
+pub fn shared_item(rucksack: &str) -> anyhow::Result<char> {
+    let (first, second) = split_compartments(rucksack)?;
+    first
+        .parse::<ItemSet>()?
+        .intersection(&second.parse()?)
+        .single()
+}
+
+/// Find the badge of a group of rucksacks, whatever the size of the group
+pub fn badge(group: &[&str]) -> anyhow::Result<char> {
+    let (first, others) = group.split_first().context("Empty group")?;
+    let mut common = first.parse::<ItemSet>()?;
+    for rucksack in others {
+        common = common.intersection(&rucksack.parse()?);
+    }
+    common.single()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::item_set::{badge, shared_item, split_compartments, ItemSet};
+
+    #[test]
+    pub fn test_set_operations() {
+        let a = "abcZ".parse::<ItemSet>().unwrap();
+        let b = "cdZ".parse::<ItemSet>().unwrap();
+
+        assert_eq!(a.intersection(&b).to_string(), "cZ");
+        assert_eq!(a.union(&b).to_string(), "abcdZ");
+        assert!(a.contains('Z'));
+        assert!(!a.contains('d'));
+        assert!("ab1".parse::<ItemSet>().is_err());
+    }
+
+    #[test]
+    pub fn test_shared_item() {
+        assert_eq!(shared_item("vJrwpWtwJgWrhcsFMMfFFhFp").unwrap(), 'p');
+        assert!(shared_item("abc").is_err());
+        assert!(shared_item("abcd").is_err());
+        assert_eq!(
+            shared_item("aéb").unwrap_err().to_string(),
+            "Rucksack \"aéb\" has items that are not letters"
+        );
+        assert_eq!(split_compartments("abcd").unwrap(), ("ab", "cd"));
+    }
+
+    #[test]
+    pub fn test_badge_any_group_size() {
+        assert_eq!(badge(&["abX", "cdX", "eXf", "Xgh"]).unwrap(), 'X');
+        assert_eq!(badge(&["zA", "zB", "zC", "zD", "zE"]).unwrap(), 'z');
+        assert!(badge(&["ab", "cd"]).is_err());
+        assert!(badge(&[]).is_err());
+    }
+}
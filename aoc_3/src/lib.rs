@@ -1,70 +1,84 @@
 use std::collections::HashSet;
 
+use anyhow::{bail, Context};
+
+pub use crate::audit::{audit, Audit, GroupAudit, ItemStats, RucksackAudit, SharedItem};
+pub use crate::item_set::{badge, shared_item, split_compartments, ItemSet};
+
+mod audit;
+mod item_set;
+
 pub fn part1(input_path: &str) -> anyhow::Result<u32> {
-    Ok(std::fs::read_to_string(input_path)?
-        .lines()
-        .map(|line| line.split_at(line.len() / 2)) // Split in the middle
-        .map(|(x, y)| {
-            // For each char of the first half, check that is is present in the second
-            for a in x.chars() {
-                if y.contains(a) {
-                    return a;
-                }
-            }
-            unreachable!() // happens if no common char found. Should not happen, right?
-        }) // Map to the common char
-        .map(|x| score(&x)) // Compute the score in a ugly way
-        .sum())
+    let mut sum = 0;
+    for line in std::fs::read_to_string(input_path)?.lines() {
+        sum += score(&shared_item(line)?)?;
+    }
+    Ok(sum)
 }
 
 pub fn part1_hashset(input_path: &str) -> anyhow::Result<u32> {
-    Ok(std::fs::read_to_string(input_path)?
+    std::fs::read_to_string(input_path)?
         .lines()
-        .map(|line| line.split_at(line.len() / 2)) // Split in the middle
-        .map(|(x, y)| {
-            (
-                x.chars().collect::<HashSet<char>>(),
-                y.chars().collect::<HashSet<char>>(),
-            )
+        .map(split_compartments) // Split in the middle
+        .map(|parts| {
+            parts.map(|(x, y)| {
+                (
+                    x.chars().collect::<HashSet<char>>(),
+                    y.chars().collect::<HashSet<char>>(),
+                )
+            })
         }) // Map both part as HashSet, to use the intersection method
-        .map(|(x, y)| *x.intersection(&y).collect::<Vec<&char>>()[0]) // What can possibly go wrong if there is no intersection? Everything.
-        .map(|x| score(&x)) // Compute the score in a ugly way
-        .sum())
+        .map(|parts| {
+            parts.and_then(|(x, y)| {
+                x.intersection(&y)
+                    .next()
+                    .copied()
+                    .context("No common item found")
+            })
+        }) // Take the common char, if there is one
+        .map(|x| x.and_then(|x| score(&x))) // Compute the score
+        .sum()
 }
 
 pub fn part2(input_path: &str) -> anyhow::Result<u32> {
-    Ok(std::fs::read_to_string(input_path)?
-        .lines()
-        .collect::<Vec<&str>>() // Collect as Vec of &str
-        .chunks(3) // Read 3 by 3
-        .map(|slice| {
-            // For each char of the first line of the chunk, check that it is present in both second
-            // and third line.
-            for a in slice[0].chars() {
-                if slice[1].contains(a) && slice[2].contains(a) {
-                    return a;
-                }
-            }
-            unreachable!() // It can't be. Can it?
-        }) // Check for each char of line n that it is present it line n + 1 and n + 2
-        .map(|x| score(&x)) // Compute the score
-        .sum())
+    badges(input_path, 3)
+}
+
+/// Sum the priorities of the badges of each group of `group_size` elves
+pub fn badges(input_path: &str, group_size: usize) -> anyhow::Result<u32> {
+    if group_size == 0 {
+        bail!("Group size must be at least 1");
+    }
+    let input = std::fs::read_to_string(input_path)?;
+    let lines = input.lines().collect::<Vec<&str>>();
+    if !lines.len().is_multiple_of(group_size) {
+        bail!(
+            "{} rucksacks can't be split in groups of {group_size}",
+            lines.len()
+        );
+    }
+
+    let mut sum = 0;
+    for group in lines.chunks(group_size) {
+        sum += score(&badge(group)?)?;
+    }
+    Ok(sum)
 }
 
-/// #Compute the score of a char.
+/// Compute the priority of an item: `a` to `z` are 1 to 26, `A` to `Z` are 27 to 52.
 ///
-/// I'm pretty sure there is much better way to do it.
-fn score(c: &char) -> u32 {
-    if c.is_uppercase() {
-        *c as u32 - 65 + 27 // 65 the value of A in ASCII + 27 the value priority of A in the exercice
-    } else {
-        *c as u32 - 97 + 1 // 94 the value of a in ASCII + 1 the value priority of a in the exercice
+/// Anything else is not an item type, so it is rejected.
+pub fn score(c: &char) -> anyhow::Result<u32> {
+    match c {
+        'a'..='z' => Ok(*c as u32 - 'a' as u32 + 1),
+        'A'..='Z' => Ok(*c as u32 - 'A' as u32 + 27),
+        _ => bail!("Invalid item {c:?}, items must be ASCII letters"),
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{part1, part2};
+    use crate::{badges, part1, part1_hashset, part2, score};
 
     #[test]
     pub fn test_part1() {
@@ -73,11 +87,36 @@ mod test {
 
     #[test]
     pub fn test_part1_hashset() {
-        assert_eq!(157, part1("input_test.txt").unwrap())
+        assert_eq!(157, part1_hashset("input_test.txt").unwrap());
+
+        let path = std::env::temp_dir().join(format!("aoc_3_{}", std::process::id()));
+        for rucksack in ["aéb", "abc"] {
+            std::fs::write(&path, rucksack).unwrap();
+            assert!(part1_hashset(path.to_str().unwrap()).is_err());
+        }
+        std::fs::remove_file(&path).unwrap();
     }
 
     #[test]
     pub fn test_part2() {
         assert_eq!(70, part2("input_test.txt").unwrap())
     }
+
+    #[test]
+    pub fn test_badges_group_size() {
+        // 6 rucksacks, can't be split by 4
+        assert!(badges("input_test.txt", 4).is_err());
+        assert_eq!(70, badges("input_test.txt", 3).unwrap());
+    }
+
+    #[test]
+    pub fn test_score() {
+        assert_eq!(1, score(&'a').unwrap());
+        assert_eq!(26, score(&'z').unwrap());
+        assert_eq!(27, score(&'A').unwrap());
+        assert_eq!(52, score(&'Z').unwrap());
+        assert!(score(&'0').is_err());
+        assert!(score(&'é').is_err());
+        assert!(score(&' ').is_err());
+    }
 }
@@ -1,6 +1,14 @@
-use aoc_3::{part1, part1_hashset, part2};
+use aoc_3::{audit, part1, part1_hashset, part2};
 
 fn main() {
+    if std::env::args().nth(1).as_deref() == Some("audit") {
+        match audit("input.txt", 3) {
+            Ok(report) => println!("{report}"),
+            Err(e) => eprintln!("Audit failed: {e:#}"),
+        }
+        return;
+    }
+
     println!("{}", part1("input.txt").unwrap());
     println!("{}", part1_hashset("input.txt").unwrap());
     println!("{}", part2("input.txt").unwrap());
@@ -0,0 +1,324 @@
+use std::collections::BTreeSet;
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use interval_set::IntervalSet;
+
+use crate::parse_line;
+
+/// The sections assigned to a single elf
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Assignment {
+    /// Line of the assignment in the input, starting at 1
+    pub line: usize,
+    pub sections: RangeInclusive<u32>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MalformedLine {
+    pub line: usize,
+    pub content: String,
+    pub error: String,
+}
+
+/// A run of consecutive sections covered by the same number of elves
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Coverage {
+    pub sections: RangeInclusive<u32>,
+    pub elves: usize,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Reassignment {
+    /// Elves (as index in the assignments) to take off their current sections
+    pub released: Vec<usize>,
+    /// New sections for some of the released elves, filling the holes of the coverage
+    pub moves: Vec<(usize, RangeInclusive<u32>)>,
+    /// Sections still covered by nobody once the moves are done
+    pub uncovered: IntervalSet<u32>,
+}
+
+/// All the assignments of the input, analysed together instead of line by line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Analysis {
+    pub assignments: Vec<Assignment>,
+    pub malformed: Vec<MalformedLine>,
+    /// Number of elves on each section, from the lowest assigned section to the highest
+    pub coverage: Vec<Coverage>,
+}
+
+pub fn analyse(input_path: &str) -> anyhow::Result<Analysis> {
+    Ok(Analysis::new(&std::fs::read_to_string(input_path)?))
+}
+
+impl Analysis {
+    pub fn new(input: &str) -> Self {
+        let mut assignments = vec![];
+        let mut malformed = vec![];
+        for (index, line) in input.lines().enumerate() {
+            match parse_line(line) {
+                Ok((first, second)) => {
+                    assignments.push(Assignment {
+                        line: index + 1,
+                        sections: first,
+                    });
+                    assignments.push(Assignment {
+                        line: index + 1,
+                        sections: second,
+                    });
+                }
+                Err(e) => malformed.push(MalformedLine {
+                    line: index + 1,
+                    content: line.to_owned(),
+                    error: e.to_string(),
+                }),
+            }
+        }
+
+        let coverage = sweep(&assignments);
+        Analysis {
+            assignments,
+            malformed,
+            coverage,
+        }
+    }
+
+    /// Number of elves assigned to a section
+    pub fn coverage_of(&self, section: u32) -> usize {
+        let index = self
+            .coverage
+            .partition_point(|c| *c.sections.end() < section);
+        self.coverage
+            .get(index)
+            .filter(|c| c.sections.contains(&section))
+            .map_or(0, |c| c.elves)
+    }
+
+    /// From the lowest assigned section to the highest one
+    pub fn bounds(&self) -> Option<RangeInclusive<u32>> {
+        let first = self.coverage.first()?;
+        let last = self.coverage.last()?;
+        Some(*first.sections.start()..=*last.sections.end())
+    }
+
+    /// Sections nobody is assigned to, between the lowest and the highest assigned sections
+    pub fn uncovered(&self) -> IntervalSet<u32> {
+        self.coverage
+            .iter()
+            .filter(|c| c.elves == 0)
+            .map(|c| c.sections.clone())
+            .collect()
+    }
+
+    /// Sections assigned to more than `max_elves` elves
+    pub fn covered_by_more_than(&self, max_elves: usize) -> IntervalSet<u32> {
+        self.coverage
+            .iter()
+            .filter(|c| c.elves > max_elves)
+            .map(|c| c.sections.clone())
+            .collect()
+    }
+
+    /// Every pair of elves from different lines with at least a section in common
+    pub fn overlapping_pairs(&self) -> Vec<(usize, usize)> {
+        let mut pairs = vec![];
+        let mut active: Vec<usize> = vec![];
+        for elf in self.by_start() {
+            let sections = &self.assignments[elf].sections;
+            active.retain(|other| self.assignments[*other].sections.end() >= sections.start());
+            for other in &active {
+                if self.assignments[*other].line != self.assignments[elf].line {
+                    pairs.push((elf.min(*other), elf.max(*other)));
+                }
+            }
+            active.push(elf);
+        }
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// Suggest the fewest elves to release so no section has more than `max_elves` elves, then
+    /// move them to the sections nobody covers.
+    ///
+    /// When too many elves share a section, releasing the one whose assignment ends last is
+    /// always the best choice, as it is the one most likely to cause the next conflicts.
+    pub fn reassignment(&self, max_elves: usize) -> Reassignment {
+        let mut released = vec![];
+        let mut active = BTreeSet::<(u32, usize)>::new();
+        for elf in self.by_start() {
+            let sections = &self.assignments[elf].sections;
+            active.retain(|(end, _)| end >= sections.start());
+            active.insert((*sections.end(), elf));
+            if active.len() > max_elves {
+                let (_, latest) = active.pop_last().unwrap();
+                released.push(latest);
+            }
+        }
+        released.sort_unstable();
+
+        let kept = self
+            .assignments
+            .iter()
+            .enumerate()
+            .filter(|(elf, _)| released.binary_search(elf).is_err())
+            .map(|(_, a)| a.sections.clone())
+            .collect::<IntervalSet<u32>>();
+        let mut uncovered = match self.bounds() {
+            Some(bounds) => kept.complement(bounds),
+            None => IntervalSet::new(),
+        };
+
+        let mut moves = vec![];
+        if max_elves > 0 {
+            let holes = uncovered
+                .ranges()
+                .cloned()
+                .collect::<Vec<RangeInclusive<u32>>>();
+            for (elf, hole) in released.iter().zip(holes) {
+                uncovered.remove(hole.clone());
+                moves.push((*elf, hole));
+            }
+        }
+
+        Reassignment {
+            released,
+            moves,
+            uncovered,
+        }
+    }
+
+    /// Index of the assignments, sorted by first section
+    fn by_start(&self) -> Vec<usize> {
+        let mut elves = (0..self.assignments.len()).collect::<Vec<usize>>();
+        elves.sort_by_key(|elf| *self.assignments[*elf].sections.start());
+        elves
+    }
+}
+
+/// Sweep over the start and end of every assignment to count the elves on each section
+fn sweep(assignments: &[Assignment]) -> Vec<Coverage> {
+    // +1 elf on the first section, -1 elf on the one after the last section
+    let mut events = assignments
+        .iter()
+        .flat_map(|a| {
+            [
+                (*a.sections.start() as u64, 1),
+                (*a.sections.end() as u64 + 1, -1),
+            ]
+        })
+        .collect::<Vec<(u64, i64)>>();
+    events.sort_unstable();
+
+    let mut coverage = vec![];
+    let mut elves = 0i64;
+    let mut previous: Option<u64> = None;
+    for (position, delta) in events {
+        if let Some(start) = previous.filter(|start| *start < position) {
+            coverage.push(Coverage {
+                sections: start as u32..=(position - 1) as u32,
+                elves: elves as usize,
+            });
+        }
+        elves += delta;
+        previous = Some(position);
+    }
+    coverage
+}
+
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for malformed in &self.malformed {
+            writeln!(
+                f,
+                "Malformed line {} {:?}: {}",
+                malformed.line, malformed.content, malformed.error
+            )?;
+        }
+        writeln!(f, "Coverage:")?;
+        for c in &self.coverage {
+            writeln!(
+                f,
+                "  {:>3}-{:<3}: {} elves",
+                c.sections.start(),
+                c.sections.end(),
+                c.elves
+            )?;
+        }
+        write!(
+            f,
+            "{} elves, {} overlapping pairs across lines",
+            self.assignments.len(),
+            self.overlapping_pairs().len()
+        )
+    }
+}
+
+impl fmt::Display for Reassignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "Release {} elves: {:?}",
+            self.released.len(),
+            self.released
+        )?;
+        for (elf, sections) in &self.moves {
+            writeln!(f, "  elf {elf} -> {}-{}", sections.start(), sections.end())?;
+        }
+        write!(
+            f,
+            "{} sections left uncovered",
+            self.uncovered.covered_len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use interval_set::IntervalSet;
+
+    use crate::Analysis;
+
+    #[test]
+    pub fn test_coverage() {
+        let analysis = Analysis::new(&std::fs::read_to_string("input_test.txt").unwrap());
+
+        assert_eq!(analysis.assignments.len(), 12);
+        assert!(analysis.malformed.is_empty());
+        assert_eq!(analysis.bounds(), Some(2..=9));
+        assert_eq!(analysis.coverage_of(1), 0);
+        assert_eq!(analysis.coverage_of(2), 4);
+        assert_eq!(analysis.coverage_of(6), 8);
+        assert_eq!(analysis.coverage_of(9), 1);
+        assert!(analysis.uncovered().is_empty());
+        assert_eq!(analysis.covered_by_more_than(7), IntervalSet::from(6..=6));
+    }
+
+    #[test]
+    pub fn test_pairs_and_malformed() {
+        let analysis = Analysis::new("1-3,10-12\nnope\n2-4,20-20\n11-11,30-x");
+
+        assert_eq!(analysis.assignments.len(), 4);
+        assert_eq!(analysis.malformed.len(), 2);
+        assert_eq!(analysis.malformed[0].line, 2);
+        assert_eq!(analysis.malformed[1].line, 4);
+        assert_eq!(analysis.overlapping_pairs(), vec![(0, 2)]);
+        assert_eq!(
+            analysis.uncovered(),
+            IntervalSet::from_iter([5..=9, 13..=19])
+        );
+    }
+
+    #[test]
+    pub fn test_reassignment() {
+        let analysis = Analysis::new("1-5,2-8\n3-4,10-12\n7-7,14-14");
+
+        // Sections 3 and 4 have 3 elves, releasing the 2-8 one is enough
+        let reassignment = analysis.reassignment(2);
+        assert_eq!(reassignment.released, vec![1]);
+        assert_eq!(reassignment.moves, vec![(1, 6..=6)]);
+        assert_eq!(
+            reassignment.uncovered,
+            IntervalSet::from_iter([8..=9, 13..=13])
+        );
+    }
+}
@@ -1,70 +1,61 @@
-use std::fmt::Error;
 use std::ops::RangeInclusive;
 
-use anyhow::Context;
+use anyhow::{bail, Context};
 use itertools::Itertools;
 
-trait InclusiveRange {
-    fn contains_range(&self, other: &Self) -> bool;
+pub use crate::analysis::{analyse, Analysis, Assignment, Coverage, MalformedLine, Reassignment};
+pub use crate::report::{depict, pair_reports, render_report, Containment, PairReport};
 
-    fn contains_or_is_contained(&self, other: &Self) -> bool {
-        self.contains_range(other) || other.contains_range(self)
-    }
-
-    fn contains_start(&self, other: &Self) -> bool;
-
-    /// If the range contains the start of the other range OR if the other range contains part of
-    /// the current range, then we have an overlap
-    fn overlaps_or_is_overlapped(&self, other: &Self) -> bool {
-        self.contains_start(other) || other.contains_start(self)
-    }
-}
-
-impl<T> InclusiveRange for RangeInclusive<T>
-where
-    T: PartialOrd,
-{
-    fn contains_range(&self, other: &Self) -> bool {
-        self.contains(other.start()) && self.contains(other.end())
-    }
-
-    fn contains_start(&self, other: &Self) -> bool {
-        self.contains(other.start())
-    }
-}
+mod analysis;
+mod report;
 
 fn parse_range(range: &str) -> anyhow::Result<RangeInclusive<u32>> {
     let (r1, r2) = range
         .split('-')
-        .filter_map(|x| x.parse::<u32>().ok())
-        .collect_tuple::<(u32, u32)>()
+        .collect_tuple::<(&str, &str)>()
         .context("Could not collect tuple")?;
+    let (r1, r2) = (r1.trim().parse::<u32>()?, r2.trim().parse::<u32>()?);
+    if r2 < r1 {
+        bail!("Range {range:?} ends before it starts");
+    }
     Ok(r1..=r2)
 }
+
 fn parse_line(line: &str) -> anyhow::Result<(RangeInclusive<u32>, RangeInclusive<u32>)> {
-    let (first, second) = line.split_once(',').ok_or_else(Error::default)?;
+    let (first, second) = line.split_once(',').context("Missing ','")?;
     Ok((parse_range(first)?, parse_range(second)?))
 }
 
-pub fn part1(input_path: &str) -> anyhow::Result<u32> {
-    Ok(std::fs::read_to_string(input_path)?
+/// Parse every line of the input, failing on the first malformed one
+fn parse_input(input: &str) -> anyhow::Result<Vec<(RangeInclusive<u32>, RangeInclusive<u32>)>> {
+    input
         .lines()
-        .filter_map(|x| parse_line(x).ok())
-        .filter(|(a, b)| a.contains_or_is_contained(b))
+        .enumerate()
+        .map(|(index, line)| {
+            parse_line(line).with_context(|| format!("Line {}: {line:?}", index + 1))
+        })
+        .collect()
+}
+
+pub fn part1(input_path: &str) -> anyhow::Result<u32> {
+    Ok(parse_input(&std::fs::read_to_string(input_path)?)?
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (a, b))| PairReport::new(index + 1, a.clone(), b.clone()).fully_contains())
         .count() as u32)
 }
 
 pub fn part2(input_path: &str) -> anyhow::Result<u32> {
-    Ok(std::fs::read_to_string(input_path)?
-        .lines()
-        .filter_map(|x| parse_line(x).ok())
-        .filter(|(a, b)| a.overlaps_or_is_overlapped(b))
+    Ok(parse_input(&std::fs::read_to_string(input_path)?)?
+        .into_iter()
+        .enumerate()
+        .filter(|(index, (a, b))| PairReport::new(index + 1, a.clone(), b.clone()).overlaps())
         .count() as u32)
 }
 
 #[cfg(test)]
 mod test {
-    use crate::{part1, part2};
+    use crate::{parse_input, part1, part2};
 
     #[test]
     pub fn test_part1() {
@@ -75,4 +66,13 @@ mod test {
     pub fn test_part2() {
         assert!(matches!(part2("input_test.txt"), Ok(4)));
     }
+
+    #[test]
+    pub fn test_malformed_lines() {
+        assert!(parse_input("2-4,6-8\n2-4;6-8").is_err());
+        assert!(parse_input("2-4,6-x").is_err());
+        assert!(parse_input("4-2,6-8").is_err());
+        assert!(parse_input("1-2-3,6-8").is_err());
+        assert_eq!(parse_input("2-4,6-8").unwrap(), vec![(2..=4, 6..=8)]);
+    }
 }
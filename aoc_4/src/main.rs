@@ -1,11 +1,35 @@
-use aoc_4::{part1, part2};
+use aoc_4::{analyse, part1, part2, render_report};
 
 fn main() {
-    if let Ok(result) = part1("input.txt") {
-        println!("{result}");
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("analysis") {
+        // Maximum number of elves on a single section, for the reassignment suggestion
+        let max_elves = args.get(2).and_then(|x| x.parse().ok()).unwrap_or(2);
+        match analyse("input.txt") {
+            Ok(analysis) => {
+                println!("{analysis}");
+                println!("{}", analysis.reassignment(max_elves));
+            }
+            Err(e) => eprintln!("{e:#}"),
+        }
+        return;
     }
 
-    if let Ok(result) = part2("input.txt") {
-        println!("{result}");
+    if args.get(1).map(String::as_str) == Some("report") {
+        match render_report("input.txt") {
+            Ok(report) => print!("{report}"),
+            Err(e) => eprintln!("{e:#}"),
+        }
+        return;
+    }
+
+    match part1("input.txt") {
+        Ok(result) => println!("{result}"),
+        Err(e) => eprintln!("{e:#}"),
+    }
+
+    match part2("input.txt") {
+        Ok(result) => println!("{result}"),
+        Err(e) => eprintln!("{e:#}"),
     }
 }
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use anyhow::Context;
+use interval_set::IntervalSet;
+
+use crate::parse_line;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Containment {
+    /// Neither assignment contains the other
+    None,
+    FirstContainsSecond,
+    SecondContainsFirst,
+    /// Both elves have the very same sections
+    Equal,
+}
+
+/// Everything there is to know about the two assignments of a line
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PairReport {
+    /// Line of the pair in the input, starting at 1
+    pub line: usize,
+    pub first: RangeInclusive<u32>,
+    pub second: RangeInclusive<u32>,
+    pub containment: Containment,
+    /// Sections assigned to both elves, if any
+    pub overlap: Option<RangeInclusive<u32>>,
+}
+
+impl PairReport {
+    pub fn new(line: usize, first: RangeInclusive<u32>, second: RangeInclusive<u32>) -> Self {
+        let first_set = IntervalSet::from(first.clone());
+        let second_set = IntervalSet::from(second.clone());
+        let containment = match (
+            first_set.contains_range(&second),
+            second_set.contains_range(&first),
+        ) {
+            (true, true) => Containment::Equal,
+            (true, false) => Containment::FirstContainsSecond,
+            (false, true) => Containment::SecondContainsFirst,
+            (false, false) => Containment::None,
+        };
+        // The intersection of two ranges is at most a single range
+        let overlap = first_set.intersection(&second_set).ranges().next().cloned();
+
+        PairReport {
+            line,
+            first,
+            second,
+            containment,
+            overlap,
+        }
+    }
+
+    pub fn first_size(&self) -> u64 {
+        u64::from(*self.first.end()) - u64::from(*self.first.start()) + 1
+    }
+
+    pub fn second_size(&self) -> u64 {
+        u64::from(*self.second.end()) - u64::from(*self.second.start()) + 1
+    }
+
+    /// One of the assignment fully contains the other, what part 1 is looking for
+    pub fn fully_contains(&self) -> bool {
+        self.containment != Containment::None
+    }
+
+    /// The assignments have at least a section in common, what part 2 is looking for
+    pub fn overlaps(&self) -> bool {
+        self.overlap.is_some()
+    }
+}
+
+/// Draw sections 0 to `last_section` like the puzzle does: the last digit of the section number
+/// if it is in the range, a `.` otherwise. `2-4` on 9 sections gives `..234.....`.
+pub fn depict(range: &RangeInclusive<u32>, last_section: u32) -> String {
+    (0..=last_section)
+        .map(|section| {
+            if range.contains(&section) {
+                char::from_digit(section % 10, 10).unwrap()
+            } else {
+                '.'
+            }
+        })
+        .collect()
+}
+
+/// Report every line of the input, one by one
+pub fn pair_reports(input: &str) -> impl Iterator<Item = anyhow::Result<PairReport>> + '_ {
+    input.lines().enumerate().map(|(index, line)| {
+        let (first, second) =
+            parse_line(line).with_context(|| format!("Line {}: {line:?}", index + 1))?;
+        Ok(PairReport::new(index + 1, first, second))
+    })
+}
+
+/// Render every pair of the input with its diagrams, as a text report
+pub fn render_report(input_path: &str) -> anyhow::Result<String> {
+    let reports = pair_reports(&std::fs::read_to_string(input_path)?)
+        .collect::<anyhow::Result<Vec<PairReport>>>()?;
+    let last_section = reports
+        .iter()
+        .map(|r| *r.first.end().max(r.second.end()))
+        .max()
+        .unwrap_or_default();
+
+    let mut output = String::new();
+    for report in &reports {
+        output.push_str(&format!(
+            "Line {}\n{}  {}\n{}  {}\n{report}\n\n",
+            report.line,
+            depict(&report.first, last_section),
+            format_range(&report.first),
+            depict(&report.second, last_section),
+            format_range(&report.second),
+        ));
+    }
+    Ok(output)
+}
+
+fn format_range(range: &RangeInclusive<u32>) -> String {
+    format!("{}-{}", range.start(), range.end())
+}
+
+impl fmt::Display for PairReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sizes {} and {}, ",
+            self.first_size(),
+            self.second_size()
+        )?;
+        match self.containment {
+            Containment::Equal => write!(f, "same sections, ")?,
+            Containment::FirstContainsSecond => write!(
+                f,
+                "{} contains {}, ",
+                format_range(&self.first),
+                format_range(&self.second)
+            )?,
+            Containment::SecondContainsFirst => write!(
+                f,
+                "{} contains {}, ",
+                format_range(&self.second),
+                format_range(&self.first)
+            )?,
+            Containment::None => {}
+        }
+        match &self.overlap {
+            Some(overlap) => write!(f, "overlap on {}", format_range(overlap)),
+            None => write!(f, "no overlap"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::report::{depict, pair_reports, Containment};
+
+    #[test]
+    pub fn test_depict() {
+        assert_eq!(depict(&(2..=4), 9), "..234.....");
+        assert_eq!(depict(&(6..=8), 9), "......678.");
+        assert_eq!(depict(&(9..=11), 12), ".........901.");
+        assert_eq!(depict(&(0..=1), 3), "01..");
+    }
+
+    #[test]
+    pub fn test_pair_reports() {
+        let reports = pair_reports("2-4,6-8\n2-8,3-7\n6-6,4-6\n5-7,7-9\n3-3,3-3")
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap();
+
+        assert_eq!(reports[0].containment, Containment::None);
+        assert_eq!(reports[0].overlap, None);
+        assert_eq!(reports[1].containment, Containment::FirstContainsSecond);
+        assert_eq!(reports[1].overlap, Some(3..=7));
+        assert_eq!(reports[2].containment, Containment::SecondContainsFirst);
+        assert_eq!((reports[2].first_size(), reports[2].second_size()), (1, 3));
+        assert_eq!(reports[3].overlap, Some(7..=7));
+        assert_eq!(reports[4].containment, Containment::Equal);
+        assert_eq!(
+            reports[1].to_string(),
+            "sizes 7 and 5, 2-8 contains 3-7, overlap on 3-7"
+        );
+
+        assert!(pair_reports("2-4,6-8\n2-4").nth(1).unwrap().is_err());
+
+        let whole = pair_reports("0-4294967295,7-7").next().unwrap().unwrap();
+        assert_eq!(whole.first_size(), 1 << 32);
+    }
+}
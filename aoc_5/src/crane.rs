@@ -0,0 +1,130 @@
+use crate::{Move, Stacks};
+
+/// A crane model, deciding how crates are moved from a stack to another
+pub trait CrateMover {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move);
+}
+
+/// Moves crates one at a time, so a batch ends up reversed
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CrateMover9000;
+
+impl CrateMover for CrateMover9000 {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move) {
+        for _ in 0..m.number {
+            let c = stacks[m.from].pop().unwrap();
+            stacks[m.to].push(c);
+        }
+    }
+}
+
+/// Moves all the crates at once, keeping their order
+#[derive(Debug, Default, Copy, Clone)]
+pub struct CrateMover9001;
+
+impl CrateMover for CrateMover9001 {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move) {
+        let crates = stacks.take(m.from, m.number);
+        stacks[m.to].extend(crates);
+    }
+}
+
+/// Moves at most `capacity` crates at once, keeping the order of each batch.
+///
+/// A capacity of 1 is a 9000, an unlimited one is a 9001.
+#[derive(Debug, Copy, Clone)]
+pub struct CapacityLimited {
+    pub capacity: usize,
+}
+
+impl CrateMover for CapacityLimited {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move) {
+        let mut remaining = m.number;
+        while remaining > 0 {
+            let batch = remaining.min(self.capacity.max(1));
+            let crates = stacks.take(m.from, batch);
+            stacks[m.to].extend(crates);
+            remaining -= batch;
+        }
+    }
+}
+
+/// Picks crates from the bottom of the stack, one at a time, and drops them on top of the other
+#[derive(Debug, Default, Copy, Clone)]
+pub struct BottomCrane;
+
+impl CrateMover for BottomCrane {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move) {
+        let crates = stacks[m.from].drain(..m.number).collect::<Vec<char>>();
+        stacks[m.to].extend(crates);
+    }
+}
+
+/// Wraps another crane, keeping track of how much its moves cost
+#[derive(Debug, Copy, Clone)]
+pub struct CostTracking<M> {
+    pub mover: M,
+    /// Paid once per move, whatever the number of crates
+    pub cost_per_move: u64,
+    /// Paid for each crate moved
+    pub cost_per_crate: u64,
+    pub total: u64,
+}
+
+impl<M: CrateMover> CostTracking<M> {
+    pub fn new(mover: M, cost_per_move: u64, cost_per_crate: u64) -> Self {
+        CostTracking {
+            mover,
+            cost_per_move,
+            cost_per_crate,
+            total: 0,
+        }
+    }
+}
+
+impl<M: CrateMover> CrateMover for CostTracking<M> {
+    fn apply(&mut self, stacks: &mut Stacks, m: &Move) {
+        self.mover.apply(stacks, m);
+        self.total += self.cost_per_move + self.cost_per_crate * m.number as u64;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::crane::{
+        BottomCrane, CapacityLimited, CostTracking, CrateMover, CrateMover9000, CrateMover9001,
+    };
+    use crate::{Move, Stacks};
+
+    fn apply(mover: &mut impl CrateMover) -> Stacks {
+        let mut stacks = Stacks::new(vec![vec!['A', 'B', 'C', 'D', 'E'], vec![]]);
+        mover.apply(
+            &mut stacks,
+            &Move {
+                number: 4,
+                from: 1,
+                to: 2,
+            },
+        );
+        stacks
+    }
+
+    #[test]
+    pub fn test_crane_models() {
+        assert_eq!(apply(&mut CrateMover9000)[2], vec!['E', 'D', 'C', 'B']);
+        assert_eq!(apply(&mut CrateMover9001)[2], vec!['B', 'C', 'D', 'E']);
+        assert_eq!(
+            apply(&mut CapacityLimited { capacity: 3 })[2],
+            vec!['C', 'D', 'E', 'B']
+        );
+        assert_eq!(apply(&mut BottomCrane)[2], vec!['A', 'B', 'C', 'D']);
+        assert_eq!(apply(&mut BottomCrane)[1], vec!['E']);
+    }
+
+    #[test]
+    pub fn test_cost() {
+        let mut mover = CostTracking::new(CrateMover9001, 10, 2);
+        assert_eq!(apply(&mut mover)[2], vec!['B', 'C', 'D', 'E']);
+        assert_eq!(mover.total, 18);
+    }
+}
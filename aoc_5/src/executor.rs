@@ -0,0 +1,233 @@
+use std::fmt;
+
+use crate::{parse_input, CrateMover, Move, Stacks};
+
+/// Why a move can't be applied. Moves are numbered from 1, in the order of the input.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum MoveError {
+    NoSuchStack {
+        move_number: usize,
+        stack: usize,
+        stacks: usize,
+    },
+    NotEnoughCrates {
+        move_number: usize,
+        from: usize,
+        requested: usize,
+        available: usize,
+    },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MoveError::NoSuchStack {
+                move_number,
+                stack,
+                stacks,
+            } => write!(
+                f,
+                "Move {move_number}: stack {stack} does not exist, there are {stacks} stacks"
+            ),
+            MoveError::NotEnoughCrates {
+                move_number,
+                from,
+                requested,
+                available,
+            } => write!(
+                f,
+                "Move {move_number}: can't move {requested} crates from stack {from}, it only has {available}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Checks every move before handing it to the crane, so a bad move is an error and not a panic
+#[derive(Debug, Copy, Clone)]
+pub struct Executor {
+    /// Move as many crates as there are instead of failing when a stack runs out
+    pub lenient: bool,
+    /// Shown instead of the top crate of an empty stack
+    pub placeholder: char,
+}
+
+impl Default for Executor {
+    fn default() -> Self {
+        Executor {
+            lenient: false,
+            placeholder: ' ',
+        }
+    }
+}
+
+impl Executor {
+    /// Check a move against the stacks, returning the move that can actually be applied
+    pub fn validate(
+        &self,
+        stacks: &Stacks,
+        move_number: usize,
+        m: &Move,
+    ) -> Result<Move, MoveError> {
+        for stack in [m.from, m.to] {
+            if stacks.get(stack).is_none() {
+                return Err(MoveError::NoSuchStack {
+                    move_number,
+                    stack,
+                    stacks: stacks.len(),
+                });
+            }
+        }
+
+        let available = stacks[m.from].len();
+        if m.number <= available {
+            Ok(*m)
+        } else if self.lenient {
+            Ok(Move {
+                number: available,
+                ..*m
+            })
+        } else {
+            Err(MoveError::NotEnoughCrates {
+                move_number,
+                from: m.from,
+                requested: m.number,
+                available,
+            })
+        }
+    }
+
+    /// Validate then apply a single move
+    pub fn apply(
+        &self,
+        stacks: &mut Stacks,
+        move_number: usize,
+        m: &Move,
+        mover: &mut impl CrateMover,
+    ) -> Result<(), MoveError> {
+        let m = self.validate(stacks, move_number, m)?;
+        mover.apply(stacks, &m);
+        Ok(())
+    }
+
+    /// Apply all the moves, stopping at the first invalid one
+    pub fn execute(
+        &self,
+        stacks: &mut Stacks,
+        moves: &[Move],
+        mover: &mut impl CrateMover,
+    ) -> Result<(), MoveError> {
+        for (index, m) in moves.iter().enumerate() {
+            self.apply(stacks, index + 1, m, mover)?;
+        }
+        Ok(())
+    }
+
+    /// Parse the input, apply all the moves with the given crane, and read the top of the stacks
+    pub fn run(&self, input_path: &str, mover: &mut impl CrateMover) -> anyhow::Result<String> {
+        let (mut stacks, moves) = parse_input(&std::fs::read_to_string(input_path)?)?;
+        self.execute(&mut stacks, &moves, mover)?;
+        Ok(self.tops(&stacks))
+    }
+
+    /// The crate on top of each stack, with the placeholder for the empty ones
+    pub fn tops(&self, stacks: &Stacks) -> String {
+        stacks
+            .tops()
+            .map(|c| c.unwrap_or(self.placeholder))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CrateMover9000, CrateMover9001, Executor, Move, MoveError, Stacks};
+
+    fn sample() -> Stacks {
+        Stacks::new(vec![vec!['A', 'B'], vec!['C'], vec![]])
+    }
+
+    #[test]
+    pub fn test_strict() {
+        let executor = Executor::default();
+        let moves = [
+            Move {
+                number: 1,
+                from: 1,
+                to: 3,
+            },
+            Move {
+                number: 2,
+                from: 1,
+                to: 2,
+            },
+        ];
+
+        let mut stacks = sample();
+        let error = executor
+            .execute(&mut stacks, &moves, &mut CrateMover9000)
+            .unwrap_err();
+        assert_eq!(
+            error,
+            MoveError::NotEnoughCrates {
+                move_number: 2,
+                from: 1,
+                requested: 2,
+                available: 1
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Move 2: can't move 2 crates from stack 1, it only has 1"
+        );
+
+        let mut stacks = sample();
+        let error = executor
+            .apply(
+                &mut stacks,
+                7,
+                &Move {
+                    number: 1,
+                    from: 1,
+                    to: 4,
+                },
+                &mut CrateMover9000,
+            )
+            .unwrap_err();
+        assert_eq!(
+            error,
+            MoveError::NoSuchStack {
+                move_number: 7,
+                stack: 4,
+                stacks: 3
+            }
+        );
+        // Nothing moved
+        assert_eq!(stacks, sample());
+    }
+
+    #[test]
+    pub fn test_lenient_and_placeholder() {
+        let executor = Executor {
+            lenient: true,
+            placeholder: '_',
+        };
+        let mut stacks = sample();
+        executor
+            .apply(
+                &mut stacks,
+                1,
+                &Move {
+                    number: 5,
+                    from: 1,
+                    to: 2,
+                },
+                &mut CrateMover9001,
+            )
+            .unwrap();
+
+        assert_eq!(stacks[2], vec!['C', 'A', 'B']);
+        assert_eq!(executor.tops(&stacks), "_B_");
+    }
+}
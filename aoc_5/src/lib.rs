@@ -1,31 +1,46 @@
-use std::collections::HashMap;
 use std::fmt;
 
-use anyhow::Context;
-use itertools::Itertools;
-
-fn parse_initials_stacks(stacks: &str) -> anyhow::Result<HashMap<usize, Vec<char>>> {
-    let mut map: HashMap<usize, Vec<char>> = HashMap::new();
-    for line in stacks.lines().rev().skip(1) {
-        let mut index = 1usize;
-        line.chars().collect::<Vec<char>>().chunks(4).for_each(|x| {
-            let x = x.iter().collect::<String>();
-            let mut m: Vec<&str> = x.matches(char::is_alphabetic).collect();
-            if let Some(c) = m.pop() {
-                map.entry(index)
-                    .or_default()
-                    .push(c.chars().next().unwrap());
+use anyhow::{bail, Context};
+
+pub use crate::crane::{
+    BottomCrane, CapacityLimited, CostTracking, CrateMover, CrateMover9000, CrateMover9001,
+};
+pub use crate::executor::{Executor, MoveError};
+pub use crate::planner::{to_input, Plan, Planner, Target};
+pub use crate::simulation::Simulation;
+pub use crate::stacks::Stacks;
+
+mod crane;
+mod executor;
+mod planner;
+mod simulation;
+mod stacks;
+
+/// Parse the drawing of the stacks, numbers included. This is the reverse of `Stacks`'s
+/// `Display` implementation.
+pub fn parse_initials_stacks(stacks: &str) -> anyhow::Result<Stacks> {
+    // The last line holds the numbers of the stacks
+    let (drawing, numbers) = stacks.rsplit_once('\n').unwrap_or(("", stacks));
+    let mut result = vec![vec![]; numbers.split_whitespace().count()];
+
+    for line in drawing.lines().rev() {
+        for (index, x) in line.chars().collect::<Vec<char>>().chunks(4).enumerate() {
+            if let Some(c) = x.iter().find(|c| c.is_alphabetic()) {
+                result
+                    .get_mut(index)
+                    .with_context(|| format!("Crate {c} is not above a numbered stack"))?
+                    .push(*c);
             }
-            index += 1;
-        });
+        }
     }
-    Ok(map)
+    Ok(Stacks::new(result))
 }
 
-struct Move {
-    number: usize,
-    from: usize,
-    to: usize,
+#[derive(Copy, Clone, Eq, PartialEq)]
+pub struct Move {
+    pub number: usize,
+    pub from: usize,
+    pub to: usize,
 }
 
 impl fmt::Debug for Move {
@@ -38,81 +53,56 @@ impl fmt::Debug for Move {
     }
 }
 
+/// The move the way the puzzle writes it, `move 1 from 2 to 1`
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.number, self.from, self.to)
+    }
+}
+
 /// Reads the "moves" part of the file
 fn read_moves(moves: &str) -> anyhow::Result<Vec<Move>> {
-    Ok(moves
+    moves
         .lines()
-        .map(|line| {
-            let line = line.replace(char::is_alphabetic, "");
-            let mut split_line = line.split_whitespace();
-            let number = split_line.next().unwrap().parse::<usize>().unwrap();
-            let from = split_line.next().unwrap().parse::<usize>().unwrap();
-            let to = split_line.next().unwrap().parse::<usize>().unwrap();
-
-            Move { number, from, to }
+        .enumerate()
+        .map(|(index, line)| {
+            let numbers = line
+                .replace(char::is_alphabetic, "")
+                .split_whitespace()
+                .map(str::parse::<usize>)
+                .collect::<Result<Vec<usize>, _>>();
+            match numbers.as_deref() {
+                Ok(&[number, from, to]) => Ok(Move { number, from, to }),
+                _ => bail!("Move {}: can't read {line:?}", index + 1),
+            }
         })
-        .collect())
+        .collect()
 }
 
-fn moves_crates_9000(stacks: &mut HashMap<usize, Vec<char>>, m: &Move) {
-    for _ in 0..m.number {
-        //TODO: use drain() instead?
-        let c = stacks.get_mut(&(m.from)).unwrap().pop().unwrap();
-        stacks.get_mut(&(m.to)).unwrap().push(c);
-    }
+/// Parse both the drawing and the moves of the input
+fn parse_input(input: &str) -> anyhow::Result<(Stacks, Vec<Move>)> {
+    let (initial_stacks, moves) = input.split_once("\n\n").context("Could not split input")?;
+    Ok((parse_initials_stacks(initial_stacks)?, read_moves(moves)?))
 }
 
-fn moves_crates_9001(stacks: &mut HashMap<usize, Vec<char>>, m: &Move) {
-    let mut crates = Vec::with_capacity(m.number);
-    for _ in 0..m.number {
-        //TODO: use drain() instead?
-        let c = stacks.get_mut(&(m.from)).unwrap().pop().unwrap();
-        crates.push(c);
-    }
-    crates.reverse();
-
-    for c in crates {
-        stacks.get_mut(&(m.to)).unwrap().push(c);
-    }
+/// Parse the input, apply all the moves with the given crane, and read the top of the stacks.
+///
+/// Invalid moves are errors and empty stacks show up as spaces, see `Executor` to change that.
+pub fn run(input_path: &str, mover: &mut impl CrateMover) -> anyhow::Result<String> {
+    Executor::default().run(input_path, mover)
 }
 
 pub fn part1(input_path: &str) -> anyhow::Result<String> {
-    let input = std::fs::read_to_string(input_path)?;
-    let (initial_stacks, moves) = input.split_once("\n\n").context("Could not split input")?;
-
-    let mut stacks = parse_initials_stacks(initial_stacks)?;
-    read_moves(moves)?
-        .iter()
-        .for_each(|m| moves_crates_9000(&mut stacks, m));
-
-    let result = stacks
-        .keys()
-        .sorted()
-        .map(|key| stacks[key].last().unwrap())
-        .collect::<String>();
-
-    Ok(result)
+    run(input_path, &mut CrateMover9000)
 }
 
 pub fn part2(input_path: &str) -> anyhow::Result<String> {
-    let input = std::fs::read_to_string(input_path)?;
-    let (initial_stacks, moves) = input.split_once("\n\n").context("Could not split input")?;
-
-    let mut stacks = parse_initials_stacks(initial_stacks)?;
-    read_moves(moves)?
-        .iter()
-        .for_each(|m| moves_crates_9001(&mut stacks, m));
-
-    Ok(stacks
-        .keys()
-        .sorted()
-        .map(|key| stacks[key].last().unwrap())
-        .collect::<String>())
+    run(input_path, &mut CrateMover9001)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{part1, part2};
+    use crate::{part1, part2, read_moves};
 
     #[test]
     pub fn test_part1() {
@@ -123,4 +113,13 @@ mod tests {
     pub fn test_part2() {
         assert_eq!(String::from("MCD"), part2("input_test.txt").unwrap());
     }
+
+    #[test]
+    pub fn test_read_moves() {
+        let moves = read_moves("move 1 from 2 to 1\nmove 3 from 1 to 3").unwrap();
+        assert_eq!(moves[1].number, 3);
+
+        let error = read_moves("move 1 from 2 to 1\nmove 3 from 1").unwrap_err();
+        assert_eq!(error.to_string(), "Move 2: can't read \"move 3 from 1\"");
+    }
 }
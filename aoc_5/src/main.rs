@@ -1,11 +1,121 @@
-use aoc_5::{part1, part2};
+use anyhow::Context;
+use aoc_5::{
+    parse_initials_stacks, part1, part2, to_input, BottomCrane, CapacityLimited, CostTracking,
+    CrateMover, CrateMover9000, CrateMover9001, Executor, Plan, Planner, Simulation, Target,
+};
+
+/// Print the stacks after the given number of moves
+fn simulate(step: usize, executor: Executor) -> anyhow::Result<()> {
+    let input = std::fs::read_to_string("input.txt")?;
+    let mut simulation = Simulation::from_input(&input, CrateMover9000)?.with_executor(executor);
+    simulation.jump_to(step)?;
+    if let Some(m) = step.checked_sub(1).map(|i| simulation.moves()[i]) {
+        println!("After move {step}: {m:?}");
+    }
+    println!("{}", simulation.stacks());
+    Ok(())
+}
+
+/// Print an input reaching the target from the drawing of `input.txt`. The target is either the
+/// path to a drawing, or the crates wanted on top of the stacks.
+fn plan(target: &str, planner: Planner, mover: &mut impl CrateMover) -> anyhow::Result<()> {
+    let input = std::fs::read_to_string("input.txt")?;
+    let (drawing, _) = input.split_once("\n\n").context("Could not split input")?;
+    let initial = parse_initials_stacks(drawing)?;
+    let target = match std::fs::read_to_string(target) {
+        Ok(drawing) => Target::Arrangement(drawing.trim_end().parse()?),
+        Err(_) => Target::Tops(target.to_owned()),
+    };
+
+    match planner.plan(&initial, &target, mover) {
+        Plan::Found(moves) => print!("{}", to_input(&initial, &moves)),
+        Plan::Unreachable => eprintln!("The target can't be reached"),
+        Plan::LimitReached => eprintln!(
+            "Nothing found within {} moves and {} arrangements",
+            planner.max_moves, planner.max_states
+        ),
+    }
+    Ok(())
+}
 
 fn main() {
-    if let Ok(result) = part1("input.txt") {
-        println!("{result}");
+    // `--lenient` and `--placeholder=X` can go anywhere, the other arguments are positional
+    let mut executor = Executor::default();
+    let mut planner = Planner::default();
+    let mut args = vec![];
+    for arg in std::env::args().skip(1) {
+        if arg == "--lenient" {
+            executor.lenient = true;
+        } else if let Some(placeholder) = arg.strip_prefix("--placeholder=") {
+            executor.placeholder = placeholder.chars().next().unwrap_or(' ');
+        } else if let Some(Ok(limit)) = arg.strip_prefix("--limit=").map(str::parse) {
+            planner.max_moves = limit;
+        } else {
+            args.push(arg);
+        }
+    }
+
+    let crane = args.first();
+    if crane.map(String::as_str) == Some("simulate") {
+        let step = args.get(1).and_then(|x| x.parse().ok()).unwrap_or_default();
+        if let Err(e) = simulate(step, executor) {
+            eprintln!("{e:#}");
+        }
+        return;
     }
+    if crane.map(String::as_str) == Some("plan") {
+        let Some(target) = args.get(1) else {
+            eprintln!("Usage: plan TARGET [9000|9001|bottom] [--limit=N]");
+            return;
+        };
+        let result = match args.get(2).map(String::as_str) {
+            None | Some("9000") => plan(target, planner, &mut CrateMover9000),
+            Some("9001") => plan(target, planner, &mut CrateMover9001),
+            Some("bottom") => plan(target, planner, &mut BottomCrane),
+            Some(other) => Err(anyhow::anyhow!("Unknown crane {other}")),
+        };
+        if let Err(e) = result {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
+    let result = match crane.map(String::as_str) {
+        None => {
+            match part1("input.txt") {
+                Ok(result) => println!("{result}"),
+                Err(e) => eprintln!("{e:#}"),
+            }
+
+            match part2("input.txt") {
+                Ok(result) => println!("{result}"),
+                Err(e) => eprintln!("{e:#}"),
+            }
+            return;
+        }
+        Some("9000") => executor.run("input.txt", &mut CrateMover9000),
+        Some("9001") => executor.run("input.txt", &mut CrateMover9001),
+        Some("bottom") => executor.run("input.txt", &mut BottomCrane),
+        Some(capacity) if capacity.starts_with("capacity=") => {
+            match capacity["capacity=".len()..].parse() {
+                Ok(capacity) => executor.run("input.txt", &mut CapacityLimited { capacity }),
+                Err(e) => Err(e.into()),
+            }
+        }
+        Some("cost") => {
+            let mut crane = CostTracking::new(CrateMover9001, 10, 1);
+            let result = executor.run("input.txt", &mut crane);
+            println!("Cost: {}", crane.total);
+            result
+        }
+        Some(other) => {
+            eprintln!("Unknown crane {other}, expected 9000, 9001, capacity=N, bottom or cost");
+            return;
+        }
+    };
 
-    if let Ok(result) = part2("input.txt") {
-        println!("{result}");
+    match result {
+        Ok(result) => println!("{result}"),
+        Err(e) => eprintln!("{e:#}"),
     }
 }
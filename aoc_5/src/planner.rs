@@ -0,0 +1,203 @@
+use std::collections::{HashMap, VecDeque};
+
+use crate::{CrateMover, Executor, Move, Stacks};
+
+/// What the planner should reach
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Target {
+    /// Exactly these stacks
+    Arrangement(Stacks),
+    /// These crates on top of the stacks, one per stack, a space for an empty stack
+    Tops(String),
+}
+
+impl Target {
+    fn is_reached(&self, stacks: &Stacks) -> bool {
+        match self {
+            Target::Arrangement(target) => stacks == target,
+            Target::Tops(tops) => {
+                stacks.len() == tops.chars().count()
+                    && stacks.tops().map(|c| c.unwrap_or(' ')).eq(tops.chars())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Plan {
+    /// A shortest list of moves reaching the target
+    Found(Vec<Move>),
+    /// Every arrangement reachable from the initial one was tried, none matches the target
+    Unreachable,
+    /// The search hit the limits before finding anything, there may be a longer plan
+    LimitReached,
+}
+
+/// Breadth first search for the moves turning some stacks into a target
+#[derive(Debug, Copy, Clone)]
+pub struct Planner {
+    /// Don't look for plans longer than that
+    pub max_moves: usize,
+    /// Give up after looking at that many different arrangements
+    pub max_states: usize,
+}
+
+impl Default for Planner {
+    fn default() -> Self {
+        Planner {
+            max_moves: 10,
+            max_states: 1_000_000,
+        }
+    }
+}
+
+impl Planner {
+    pub fn plan(&self, initial: &Stacks, target: &Target, mover: &mut impl CrateMover) -> Plan {
+        let (stacks, counts) = crate_counts(initial);
+        let impossible = match target {
+            Target::Arrangement(target) => crate_counts(target) != (stacks, counts),
+            Target::Tops(tops) => {
+                tops.chars().count() != stacks
+                    || tops.chars().any(|c| c != ' ' && !counts.contains_key(&c))
+            }
+        };
+        if impossible {
+            return Plan::Unreachable;
+        }
+
+        // Every arrangement seen so far, with the one it comes from and the move in between
+        let mut seen: HashMap<Stacks, Option<(Stacks, Move)>> = HashMap::new();
+        let mut queue = VecDeque::from([(initial.clone(), 0)]);
+        seen.insert(initial.clone(), None);
+        let mut limited = false;
+
+        while let Some((stacks, depth)) = queue.pop_front() {
+            if target.is_reached(&stacks) {
+                return Plan::Found(backtrack(&seen, stacks));
+            }
+            if depth == self.max_moves {
+                limited = true;
+                continue;
+            }
+
+            for m in possible_moves(&stacks) {
+                let mut next = stacks.clone();
+                // Moves are valid by construction
+                if Executor::default().apply(&mut next, 1, &m, mover).is_err() {
+                    continue;
+                }
+                if seen.contains_key(&next) {
+                    continue;
+                }
+                if seen.len() >= self.max_states {
+                    return Plan::LimitReached;
+                }
+                seen.insert(next.clone(), Some((stacks.clone(), m)));
+                queue.push_back((next, depth + 1));
+            }
+        }
+
+        if limited {
+            Plan::LimitReached
+        } else {
+            Plan::Unreachable
+        }
+    }
+}
+
+/// Every move that can be made from the given stacks
+fn possible_moves(stacks: &Stacks) -> impl Iterator<Item = Move> + '_ {
+    (1..=stacks.len()).flat_map(move |from| {
+        (1..=stacks.len())
+            .filter(move |to| *to != from)
+            .flat_map(move |to| {
+                (1..=stacks[from].len()).map(move |number| Move { number, from, to })
+            })
+    })
+}
+
+fn crate_counts(stacks: &Stacks) -> (usize, HashMap<char, usize>) {
+    let mut counts = HashMap::new();
+    for c in stacks.iter().flatten() {
+        *counts.entry(*c).or_default() += 1;
+    }
+    (stacks.len(), counts)
+}
+
+/// Walk back from the target to the initial arrangement to list the moves
+fn backtrack(seen: &HashMap<Stacks, Option<(Stacks, Move)>>, mut stacks: Stacks) -> Vec<Move> {
+    let mut moves = vec![];
+    while let Some(Some((previous, m))) = seen.get(&stacks) {
+        moves.push(*m);
+        stacks = previous.clone();
+    }
+    moves.reverse();
+    moves
+}
+
+/// Write a puzzle input, drawing and moves, that `part1` and `part2` can read
+pub fn to_input(initial: &Stacks, moves: &[Move]) -> String {
+    let mut input = format!("{initial}\n\n");
+    for m in moves {
+        input.push_str(&format!("{m}\n"));
+    }
+    input
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::planner::to_input;
+    use crate::{parse_input, CrateMover9000, CrateMover9001, Executor, Plan, Planner, Target};
+
+    #[test]
+    pub fn test_plan_tops() {
+        let input = std::fs::read_to_string("input_test.txt").unwrap();
+        let (initial, _) = parse_input(&input).unwrap();
+
+        // The example takes 4 moves, but there is a shorter way to get the same tops
+        let target = Target::Tops("CMZ".to_owned());
+        let Plan::Found(moves) = Planner::default().plan(&initial, &target, &mut CrateMover9000)
+        else {
+            panic!("No plan found");
+        };
+        assert_eq!(moves.len(), 2);
+
+        let (mut stacks, moves) = parse_input(&to_input(&initial, &moves)).unwrap();
+        Executor::default()
+            .execute(&mut stacks, &moves, &mut CrateMover9000)
+            .unwrap();
+        assert_eq!(Executor::default().tops(&stacks), "CMZ");
+    }
+
+    #[test]
+    pub fn test_plan_arrangement() {
+        let input = std::fs::read_to_string("input_test.txt").unwrap();
+        let (initial, moves) = parse_input(&input).unwrap();
+        let mut target = initial.clone();
+        Executor::default()
+            .execute(&mut target, &moves, &mut CrateMover9001)
+            .unwrap();
+
+        let planner = Planner::default();
+        let target = Target::Arrangement(target);
+        let Plan::Found(plan) = planner.plan(&initial, &target, &mut CrateMover9001) else {
+            panic!("No plan found");
+        };
+        assert!(plan.len() <= moves.len());
+
+        let tight = Planner {
+            max_moves: plan.len() - 1,
+            ..planner
+        };
+        assert_eq!(
+            tight.plan(&initial, &target, &mut CrateMover9001),
+            Plan::LimitReached
+        );
+
+        let impossible = Target::Tops("XYZ".to_owned());
+        assert_eq!(
+            planner.plan(&initial, &impossible, &mut CrateMover9001),
+            Plan::Unreachable
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use anyhow::bail;
+
+use crate::{parse_input, CrateMover, Executor, Move, MoveError, Stacks};
+
+/// Applies the moves one at a time, keeping every intermediate state so we can go back and forth
+pub struct Simulation<M> {
+    mover: M,
+    executor: Executor,
+    moves: Vec<Move>,
+    /// `history[n]` is the state of the stacks after `n` moves
+    history: Vec<Stacks>,
+    /// Number of moves applied to reach the current state
+    position: usize,
+}
+
+impl<M: CrateMover> Simulation<M> {
+    pub fn new(initial: Stacks, moves: Vec<Move>, mover: M) -> Self {
+        Simulation {
+            mover,
+            executor: Executor::default(),
+            moves,
+            history: vec![initial],
+            position: 0,
+        }
+    }
+
+    /// Build a simulation from a puzzle input, drawing and moves
+    pub fn from_input(input: &str, mover: M) -> anyhow::Result<Self> {
+        let (stacks, moves) = parse_input(input)?;
+        Ok(Simulation::new(stacks, moves, mover))
+    }
+
+    /// Validate the moves with this executor instead of the default, strict, one
+    pub fn with_executor(mut self, executor: Executor) -> Self {
+        self.executor = executor;
+        self
+    }
+
+    /// Apply the next move, returning it. `None` once every move is applied.
+    ///
+    /// An invalid move leaves the simulation where it was.
+    pub fn step(&mut self) -> Result<Option<&Move>, MoveError> {
+        if self.position >= self.moves.len() {
+            return Ok(None);
+        }
+        if self.history.len() == self.position + 1 {
+            self.compute_next()?;
+        }
+        self.position += 1;
+        Ok(Some(&self.moves[self.position - 1]))
+    }
+
+    /// Apply the first move not in the history yet to the last state of the history, without
+    /// changing the current position
+    fn compute_next(&mut self) -> Result<(), MoveError> {
+        let number = self.history.len();
+        let mut stacks = self.history[number - 1].clone();
+        self.executor.apply(
+            &mut stacks,
+            number,
+            &self.moves[number - 1],
+            &mut self.mover,
+        )?;
+        self.history.push(stacks);
+        Ok(())
+    }
+
+    /// Go back to the state before the last move. Returns `false` if we are at the start.
+    pub fn undo(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        true
+    }
+
+    /// Apply again a move that was undone. Returns `false` if there is nothing to redo.
+    pub fn redo(&mut self) -> bool {
+        if self.position + 1 >= self.history.len() {
+            return false;
+        }
+        self.position += 1;
+        true
+    }
+
+    /// Go to the state after `n` moves, whether it is before or after the current one
+    pub fn jump_to(&mut self, n: usize) -> anyhow::Result<()> {
+        if n > self.moves.len() {
+            bail!(
+                "There are only {} moves, can't jump to {n}",
+                self.moves.len()
+            );
+        }
+        // The current position only changes once every move up to `n` is known to be valid
+        while self.history.len() <= n {
+            self.compute_next()?;
+        }
+        self.position = n;
+        Ok(())
+    }
+
+    /// Apply all the remaining moves, stopping at the first invalid one
+    pub fn run(&mut self) -> Result<(), MoveError> {
+        while self.step()?.is_some() {}
+        Ok(())
+    }
+
+    /// Number of moves applied so far
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn stacks(&self) -> &Stacks {
+        &self.history[self.position]
+    }
+
+    pub fn moves(&self) -> &[Move] {
+        &self.moves
+    }
+
+    pub fn mover(&self) -> &M {
+        &self.mover
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{CrateMover9000, Simulation, Stacks};
+
+    #[test]
+    pub fn test_undo_redo_jump() {
+        let input = std::fs::read_to_string("input_test.txt").unwrap();
+        let mut simulation = Simulation::from_input(&input, CrateMover9000).unwrap();
+        let initial = simulation.stacks().clone();
+
+        assert_eq!(simulation.step().unwrap().unwrap().from, 2);
+        assert_eq!(
+            simulation.stacks().to_string(),
+            "[D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3"
+        );
+        let after_one = simulation.stacks().clone();
+
+        assert!(simulation.undo());
+        assert!(!simulation.undo());
+        assert_eq!(simulation.stacks(), &initial);
+        assert!(simulation.redo());
+        assert_eq!(simulation.stacks(), &after_one);
+        assert!(!simulation.redo());
+
+        simulation.jump_to(4).unwrap();
+        assert_eq!(
+            simulation.stacks().to_string(),
+            "        [Z]\n        [N]\n        [D]\n[C] [M] [P]\n 1   2   3"
+        );
+        assert!(simulation.step().unwrap().is_none());
+        simulation.jump_to(1).unwrap();
+        assert_eq!(simulation.stacks(), &after_one);
+        assert!(simulation.jump_to(5).is_err());
+    }
+
+    #[test]
+    pub fn test_jump_past_invalid_move() {
+        let input =
+            "[A]\n[B] [C]\n 1   2\n\nmove 1 from 1 to 2\nmove 1 from 1 to 2\nmove 1 from 1 to 2";
+        let mut simulation = Simulation::from_input(input, CrateMover9000).unwrap();
+        let initial = simulation.stacks().clone();
+
+        let error = simulation.jump_to(3).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Move 3: can't move 1 crates from stack 1, it only has 0"
+        );
+        assert_eq!(simulation.position(), 0);
+        assert_eq!(simulation.stacks(), &initial);
+
+        // The valid moves computed on the way are kept
+        assert!(simulation.redo());
+        simulation.jump_to(2).unwrap();
+        assert_eq!(simulation.position(), 2);
+        assert!(simulation.step().is_err());
+        assert_eq!(simulation.position(), 2);
+    }
+
+    #[test]
+    pub fn test_every_step_round_trips() {
+        let input = std::fs::read_to_string("input.txt").unwrap();
+        let mut simulation = Simulation::from_input(&input, CrateMover9000).unwrap();
+        loop {
+            let drawing = simulation.stacks().to_string();
+            assert_eq!(&drawing.parse::<Stacks>().unwrap(), simulation.stacks());
+            if simulation.step().unwrap().is_none() {
+                break;
+            }
+        }
+    }
+}
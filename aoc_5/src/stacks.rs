@@ -0,0 +1,118 @@
+use std::fmt;
+use std::ops::{Index, IndexMut};
+use std::str::FromStr;
+
+use crate::parse_initials_stacks;
+
+/// The stacks of crates, bottom crate first.
+///
+/// Stacks are numbered from 1 like in the puzzle, so `stacks[1]` is the leftmost one.
+#[derive(Debug, Default, Clone, Eq, PartialEq, Hash)]
+pub struct Stacks(Vec<Vec<char>>);
+
+impl Stacks {
+    pub fn new(stacks: Vec<Vec<char>>) -> Self {
+        Stacks(stacks)
+    }
+
+    /// Number of stacks
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&Vec<char>> {
+        self.0.get(index.checked_sub(1)?)
+    }
+
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut Vec<char>> {
+        self.0.get_mut(index.checked_sub(1)?)
+    }
+
+    /// Iterate over the stacks, from the leftmost one
+    pub fn iter(&self) -> impl Iterator<Item = &Vec<char>> {
+        self.0.iter()
+    }
+
+    /// Remove the `count` crates on top of a stack, keeping their order
+    pub fn take(&mut self, index: usize, count: usize) -> Vec<char> {
+        let stack = &mut self[index];
+        stack.split_off(stack.len() - count)
+    }
+
+    /// The crate on top of each stack, `None` if a stack is empty
+    pub fn tops(&self) -> impl Iterator<Item = Option<char>> + '_ {
+        self.0.iter().map(|stack| stack.last().copied())
+    }
+}
+
+impl Index<usize> for Stacks {
+    type Output = Vec<char>;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index - 1]
+    }
+}
+
+impl IndexMut<usize> for Stacks {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index - 1]
+    }
+}
+
+impl FromStr for Stacks {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_initials_stacks(s)
+    }
+}
+
+/// Draw the stacks the way the puzzle does, numbers included
+impl fmt::Display for Stacks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let height = self.0.iter().map(Vec::len).max().unwrap_or_default();
+        for level in (0..height).rev() {
+            let line = self
+                .0
+                .iter()
+                .map(|stack| match stack.get(level) {
+                    Some(c) => format!("[{c}]"),
+                    None => "   ".to_owned(),
+                })
+                .collect::<Vec<String>>()
+                .join(" ");
+            writeln!(f, "{}", line.trim_end())?;
+        }
+
+        let numbers = (1..=self.0.len())
+            .map(|index| format!(" {index} "))
+            .collect::<Vec<String>>()
+            .join(" ");
+        write!(f, "{}", numbers.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Stacks;
+
+    #[test]
+    pub fn test_drawing_round_trip() {
+        let drawing = "    [D]\n[N] [C]\n[Z] [M] [P]\n 1   2   3";
+        let stacks = drawing.parse::<Stacks>().unwrap();
+
+        assert_eq!(stacks[2], vec!['M', 'C', 'D']);
+        assert_eq!(stacks.to_string(), drawing);
+
+        let stacks = Stacks::new(vec![vec![], vec!['A'], vec![]]);
+        assert_eq!(stacks.to_string(), "    [A]\n 1   2   3");
+        assert_eq!(stacks.to_string().parse::<Stacks>().unwrap(), stacks);
+
+        let stacks = Stacks::new(vec![vec![], vec![]]);
+        assert_eq!(stacks.to_string().parse::<Stacks>().unwrap(), stacks);
+    }
+}
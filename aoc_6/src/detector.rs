@@ -0,0 +1,131 @@
+use std::io::{BufReader, Read};
+
+/// Looks for the first `window` consecutive bytes that are all different, one byte at a time.
+///
+/// The last `window` bytes are kept in a ring buffer, along with how many times each byte value
+/// appears in it, so every byte costs the same whatever the window length.
+#[derive(Debug, Clone)]
+pub struct Detector {
+    window: usize,
+    ring: Vec<u8>,
+    counts: [usize; 256],
+    /// Number of byte values appearing in the window
+    distinct: usize,
+    /// Number of bytes seen so far
+    position: usize,
+    marker: Option<usize>,
+}
+
+impl Detector {
+    pub fn new(window: usize) -> Self {
+        Detector {
+            window,
+            ring: vec![0; window],
+            counts: [0; 256],
+            distinct: 0,
+            position: 0,
+            marker: (window == 0).then_some(0),
+        }
+    }
+
+    /// Feed the next byte of the stream. Returns the marker position, the number of bytes read up to
+    /// the end of the marker, once it is found.
+    pub fn push(&mut self, byte: u8) -> Option<usize> {
+        if self.marker.is_some() {
+            return self.marker;
+        }
+
+        let slot = self.position % self.window;
+        if self.position >= self.window {
+            let old = self.ring[slot] as usize;
+            self.counts[old] -= 1;
+            if self.counts[old] == 0 {
+                self.distinct -= 1;
+            }
+        }
+        self.ring[slot] = byte;
+        self.counts[byte as usize] += 1;
+        if self.counts[byte as usize] == 1 {
+            self.distinct += 1;
+        }
+        self.position += 1;
+
+        if self.distinct == self.window {
+            self.marker = Some(self.position);
+        }
+        self.marker
+    }
+
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The marker position, if found so far
+    pub fn marker(&self) -> Option<usize> {
+        self.marker
+    }
+}
+
+/// Read the stream once, looking for markers of all the given lengths at the same time.
+///
+/// Returns the position of each marker, in the order of `lengths`. A stream shorter than a
+/// marker, or without any, gives `None`.
+pub fn find_markers(reader: impl Read, lengths: &[usize]) -> std::io::Result<Vec<Option<usize>>> {
+    let mut detectors = lengths
+        .iter()
+        .map(|length| Detector::new(*length))
+        .collect::<Vec<Detector>>();
+
+    for byte in BufReader::new(reader).bytes() {
+        let byte = byte?;
+        let mut done = true;
+        for detector in &mut detectors {
+            done &= detector.push(byte).is_some();
+        }
+        if done {
+            break;
+        }
+    }
+
+    Ok(detectors.iter().map(Detector::marker).collect())
+}
+
+/// Find a single marker in a stream
+pub fn find_marker(reader: impl Read, length: usize) -> std::io::Result<Option<usize>> {
+    Ok(find_markers(reader, &[length])?[0])
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::detector::{find_marker, find_markers};
+
+    #[test]
+    pub fn test_find_markers() {
+        let examples = [
+            ("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 7, 19),
+            ("bvwbjplbgvbhsrlpgdmjqwftvncz", 5, 23),
+            ("nppdvjthqldpwncqszvftbrmjlhg", 6, 23),
+            ("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 10, 29),
+            ("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 11, 26),
+        ];
+        for (input, packet, message) in examples {
+            assert_eq!(
+                find_markers(input.as_bytes(), &[4, 14]).unwrap(),
+                vec![Some(packet), Some(message)]
+            );
+        }
+    }
+
+    #[test]
+    pub fn test_edge_cases() {
+        // Shorter than the window
+        assert_eq!(find_marker("abc".as_bytes(), 4).unwrap(), None);
+        // The marker is the very last window
+        assert_eq!(find_marker("aabcd".as_bytes(), 4).unwrap(), Some(5));
+        assert_eq!(find_marker("abcd".as_bytes(), 4).unwrap(), Some(4));
+        assert_eq!(find_marker("aaaa".as_bytes(), 4).unwrap(), None);
+        assert_eq!(find_marker("".as_bytes(), 1).unwrap(), None);
+        assert_eq!(find_marker("a".as_bytes(), 1).unwrap(), Some(1));
+        assert_eq!(find_marker("abc".as_bytes(), 0).unwrap(), Some(0));
+    }
+}
@@ -0,0 +1,180 @@
+use std::ops::Range;
+
+use anyhow::bail;
+
+use crate::Detector;
+
+pub const START_OF_PACKET: usize = 4;
+pub const START_OF_MESSAGE: usize = 14;
+
+/// A marker and the payload following it, up to the next marker or the end of the stream
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Frame<'a> {
+    /// Where the marker is in the stream
+    pub marker: Range<usize>,
+    /// Where the payload is in the stream
+    pub payload_range: Range<usize>,
+    pub payload: &'a [u8],
+}
+
+/// Splits a stream into frames, each starting with a marker of `length` different bytes.
+///
+/// Once a marker is found, the search starts over right after it, so markers never overlap.
+/// Whatever comes before the first marker is not part of any frame.
+pub struct Frames<'a> {
+    data: &'a [u8],
+    length: usize,
+    /// End of the marker of the next frame, if there is one
+    next_marker: Option<usize>,
+}
+
+impl<'a> Frames<'a> {
+    pub fn new(data: &'a [u8], length: usize) -> Self {
+        Frames {
+            data,
+            length,
+            next_marker: next_marker(data, 0, length),
+        }
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let marker_end = self.next_marker?;
+        self.next_marker = next_marker(self.data, marker_end, self.length);
+        let payload_end = self
+            .next_marker
+            .map_or(self.data.len(), |end| end - self.length);
+
+        Some(Frame {
+            marker: marker_end - self.length..marker_end,
+            payload_range: marker_end..payload_end,
+            payload: &self.data[marker_end..payload_end],
+        })
+    }
+}
+
+/// End of the first marker found from `start`
+fn next_marker(data: &[u8], start: usize, length: usize) -> Option<usize> {
+    // An empty marker would be found again and again at the same place
+    if length == 0 {
+        return None;
+    }
+    let mut detector = Detector::new(length);
+    data[start..]
+        .iter()
+        .find_map(|byte| detector.push(*byte))
+        .map(|end| start + end)
+}
+
+/// A stream split both ways, on its start-of-packet markers and on its start-of-message markers
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Datastream<'a> {
+    pub packets: Vec<Frame<'a>>,
+    pub messages: Vec<Frame<'a>>,
+}
+
+impl<'a> Datastream<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Datastream {
+            packets: Frames::new(data, START_OF_PACKET).collect(),
+            messages: Frames::new(data, START_OF_MESSAGE).collect(),
+        }
+    }
+}
+
+/// Start of every marker of the given length in the stream
+pub fn marker_positions(data: &[u8], length: usize) -> Vec<usize> {
+    Frames::new(data, length).map(|f| f.marker.start).collect()
+}
+
+/// Build a stream with a marker of `length` bytes before each payload, that `Frames` splits back
+/// into the very same payloads.
+///
+/// A payload can't hold `length` different bytes in a row, it would be read as a marker.
+pub fn encode(payloads: &[&[u8]], length: usize) -> anyhow::Result<Vec<u8>> {
+    if length == 0 {
+        bail!("Markers can't be empty");
+    }
+
+    let mut stream = vec![];
+    for (index, payload) in payloads.iter().enumerate() {
+        if let Some(end) = next_marker(payload, 0, length) {
+            bail!("Payload {index} has a marker at {}..{end}", end - length);
+        }
+
+        // Starting the marker with the last byte of the previous payload makes sure no window
+        // across the two is read as a marker
+        let mut marker = stream.last().copied().into_iter().collect::<Vec<u8>>();
+        let candidates = (b'a'..=b'z')
+            .chain(b'A'..=b'Z')
+            .chain(b'0'..=b'9')
+            .chain(0..=u8::MAX);
+        for byte in candidates {
+            if marker.len() == length {
+                break;
+            }
+            if !marker.contains(&byte) {
+                marker.push(byte);
+            }
+        }
+        if marker.len() < length {
+            bail!("A marker can't have more than 256 different bytes");
+        }
+
+        stream.extend(marker);
+        stream.extend_from_slice(payload);
+    }
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::framing::{
+        encode, marker_positions, Datastream, Frames, START_OF_MESSAGE, START_OF_PACKET,
+    };
+
+    #[test]
+    pub fn test_frames() {
+        let data = b"mjqjpqmgbljsphdztnvjfqwrcgsmlb";
+        assert_eq!(
+            marker_positions(data, START_OF_PACKET),
+            vec![3, 7, 11, 15, 19, 23]
+        );
+
+        let frames = Frames::new(data, START_OF_PACKET).collect::<Vec<_>>();
+        assert_eq!(frames[0].marker, 3..7);
+        assert_eq!(frames[0].payload, b"");
+        assert_eq!(frames.last().unwrap().payload, b"mlb");
+
+        let frames = Frames::new(data, START_OF_MESSAGE).collect::<Vec<_>>();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].payload_range, 19..30);
+
+        let stream = Datastream::new(data);
+        assert_eq!(stream.packets.len(), 6);
+        assert_eq!(stream.packets[1].marker, 7..11);
+        assert_eq!(stream.messages, frames);
+    }
+
+    #[test]
+    pub fn test_round_trip() {
+        let payloads: [&[u8]; 5] = [b"hello", b"", b"aaa", b"abcabc", b"a"];
+        for length in [START_OF_PACKET, START_OF_MESSAGE] {
+            let stream = encode(&payloads, length).unwrap();
+            let decoded = Frames::new(&stream, length)
+                .map(|f| f.payload)
+                .collect::<Vec<_>>();
+            assert_eq!(decoded, payloads);
+        }
+
+        let payloads: [&[u8]; 3] = [b"aaa", b"", b"b"];
+        let stream = encode(&payloads, 2).unwrap();
+        assert_eq!(stream, b"abaaaabbab");
+        assert!(Frames::new(&stream, 2).map(|f| f.payload).eq(payloads));
+
+        assert!(encode(&[b"xabcd"], START_OF_PACKET).is_err());
+    }
+}
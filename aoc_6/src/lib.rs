@@ -1,8 +1,18 @@
 use std::collections::HashSet;
 
+pub use crate::detector::{find_marker, find_markers, Detector};
+pub use crate::framing::{
+    encode, marker_positions, Datastream, Frame, Frames, START_OF_MESSAGE, START_OF_PACKET,
+};
+
+mod detector;
+mod framing;
+
 pub enum Version {
     LoopVersion,
     IteratorVersion,
+    /// Single pass over the file, see `Detector`
+    StreamVersion,
 }
 
 pub fn solution(input_path: &str, version: Version, packet_length: usize) -> Option<usize> {
@@ -13,11 +23,15 @@ pub fn solution(input_path: &str, version: Version, packet_length: usize) -> Opt
         Version::IteratorVersion => {
             find_header_index_iter(&std::fs::read_to_string(input_path).ok()?, packet_length)
         }
+        Version::StreamVersion => {
+            find_marker(std::fs::File::open(input_path).ok()?, packet_length).ok()?
+        }
     }
 }
 
 fn find_header_index(input: &str, packet_length: usize) -> Option<usize> {
-    for i in 0..input.len() - packet_length {
+    // The last window starts at `input.len() - packet_length`, if the input is long enough
+    for i in 0..(input.len() + 1).saturating_sub(packet_length) {
         let s = &input[i..i + packet_length];
         let set = s.chars().collect::<HashSet<char>>();
         if set.len() == packet_length {
@@ -134,4 +148,11 @@ mod tests {
             Some(26)
         );
     }
+
+    #[test]
+    pub fn test_short_input() {
+        assert_eq!(find_header_index("abc", 4), None);
+        assert_eq!(find_header_index("aabcd", 4), Some(5));
+        assert_eq!(find_header_index_iter("abc", 4), None);
+    }
 }
@@ -1,6 +1,40 @@
-use aoc_6::{solution, Version};
+use aoc_6::{find_markers, solution, Datastream, Version};
 
 fn main() {
+    // `frames [packet|message]` splits the input instead, both ways if no marker is given
+    if let Some("frames") = std::env::args().nth(1).as_deref() {
+        let data = match std::fs::read("input.txt") {
+            Ok(data) => data,
+            Err(e) => {
+                eprintln!("{e:#}");
+                return;
+            }
+        };
+        let stream = Datastream::new(&data);
+        let sections = match std::env::args().nth(2).as_deref() {
+            None => vec![("Packets", stream.packets), ("Messages", stream.messages)],
+            Some("packet") => vec![("Packets", stream.packets)],
+            Some("message") => vec![("Messages", stream.messages)],
+            Some(other) => {
+                eprintln!("Unknown marker {other}, expected packet or message");
+                return;
+            }
+        };
+        for (title, frames) in sections {
+            println!("{title}");
+            println!("Marker Payload");
+            for frame in frames {
+                println!(
+                    "{:>5} {:>5} {}",
+                    frame.marker.start,
+                    frame.payload_range.start,
+                    String::from_utf8_lossy(frame.payload).trim_end()
+                );
+            }
+        }
+        return;
+    }
+
     println!("Loop version");
     if let Some(result) = solution("input.txt", Version::LoopVersion, 4) {
         println!("{result}");
@@ -18,4 +52,10 @@ fn main() {
     if let Some(result) = solution("input.txt", Version::IteratorVersion, 14) {
         println!("{result}");
     }
+
+    println!("Stream version");
+    match std::fs::File::open("input.txt").map(|file| find_markers(file, &[4, 14])) {
+        Ok(Ok(markers)) => markers.iter().flatten().for_each(|x| println!("{x}")),
+        Ok(Err(e)) | Err(e) => eprintln!("{e}"),
+    }
 }
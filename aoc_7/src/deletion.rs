@@ -0,0 +1,346 @@
+use std::collections::{btree_map, BTreeMap};
+use std::rc::Rc;
+
+use crate::query::is_under;
+use crate::{Dir, Entry, FreeSpace, Node};
+
+/// What a deletion plan should use as little as possible of, the other one breaking ties
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Objective {
+    #[default]
+    Bytes,
+    Items,
+}
+
+/// Directories, and files, to delete together
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Deletion {
+    /// None of them is inside another one
+    pub paths: Vec<String>,
+    pub freed: u64,
+    /// Free space on the disk once they are deleted
+    pub free_space: u64,
+    /// Whether the plan is the best one, or a guess because the tree was too big
+    pub exact: bool,
+}
+
+/// Looks for the best set of directories to delete to get enough free space
+#[derive(Debug, Copy, Clone)]
+pub struct DeletionPlanner {
+    pub free_space: FreeSpace,
+    /// Files can be deleted on their own too
+    pub include_files: bool,
+    pub objective: Objective,
+    /// Give up on the exact search, and use a greedy one, past that many combinations
+    pub max_work: usize,
+}
+
+impl Default for DeletionPlanner {
+    fn default() -> Self {
+        DeletionPlanner {
+            free_space: FreeSpace::default(),
+            include_files: false,
+            objective: Objective::default(),
+            max_work: 1_000_000,
+        }
+    }
+}
+
+/// Paths of a choice, shared with the choices built from it so that combining two is cheap
+#[derive(Debug)]
+enum Paths {
+    One(String),
+    Both(Rc<Paths>, Rc<Paths>),
+}
+
+/// Some items to delete
+#[derive(Debug, Clone, Default)]
+struct Choice {
+    bytes: u64,
+    items: usize,
+    paths: Option<Rc<Paths>>,
+}
+
+impl Choice {
+    fn single(path: String, bytes: u64) -> Self {
+        Choice {
+            bytes,
+            items: 1,
+            paths: Some(Rc::new(Paths::One(path))),
+        }
+    }
+
+    /// Delete both
+    fn join(&self, other: &Choice) -> Self {
+        let paths = match (&self.paths, &other.paths) {
+            (Some(a), Some(b)) => Some(Rc::new(Paths::Both(a.clone(), b.clone()))),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        Choice {
+            bytes: self.bytes + other.bytes,
+            items: self.items + other.items,
+            paths,
+        }
+    }
+
+    fn paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        let mut stack = self.paths.iter().cloned().collect::<Vec<Rc<Paths>>>();
+        while let Some(node) = stack.pop() {
+            match &*node {
+                Paths::One(path) => paths.push(path.clone()),
+                Paths::Both(a, b) => {
+                    stack.push(b.clone());
+                    stack.push(a.clone());
+                }
+            }
+        }
+        paths.sort();
+        paths
+    }
+}
+
+/// Everything worth keeping about the ways to delete things under a directory
+#[derive(Debug, Default)]
+struct Frontier {
+    /// The ways that don't free enough yet, only the fewest items for a given size
+    partial: BTreeMap<u64, Choice>,
+    /// The best way that frees enough
+    complete: Option<Choice>,
+}
+
+struct TooBig;
+
+impl DeletionPlanner {
+    pub fn plan(&self, root: &Dir) -> anyhow::Result<Deletion> {
+        // Makes sure the disk can hold the required space
+        self.free_space.smallest_dir(root)?;
+        let target = self.free_space.to_free(root);
+        if target == 0 {
+            return Ok(self.deletion(root, Choice::default(), true));
+        }
+
+        let mut work = 0;
+        match self.frontier(root, "", target, &mut work) {
+            Ok(frontier) => {
+                // The root itself is always big enough
+                let choice = frontier.complete.expect("the root frees everything");
+                Ok(self.deletion(root, choice, true))
+            }
+            Err(TooBig) => Ok(self.deletion(root, self.greedy(root, target), false)),
+        }
+    }
+
+    fn deletion(&self, root: &Dir, choice: Choice, exact: bool) -> Deletion {
+        Deletion {
+            free_space: self.free_space.unused(root) + choice.bytes,
+            freed: choice.bytes,
+            paths: choice.paths(),
+            exact,
+        }
+    }
+
+    /// Which choice is the best for the objective
+    fn key(&self, choice: &Choice) -> (u64, u64) {
+        match self.objective {
+            Objective::Bytes => (choice.bytes, choice.items as u64),
+            Objective::Items => (choice.items as u64, choice.bytes),
+        }
+    }
+
+    fn keep_best(&self, best: &mut Option<Choice>, choice: Choice) {
+        if best
+            .as_ref()
+            .is_none_or(|b| self.key(&choice) < self.key(b))
+        {
+            *best = Some(choice);
+        }
+    }
+
+    fn add(&self, frontier: &mut Frontier, choice: Choice, target: u64) {
+        if choice.bytes >= target {
+            self.keep_best(&mut frontier.complete, choice);
+            return;
+        }
+        match frontier.partial.entry(choice.bytes) {
+            btree_map::Entry::Vacant(slot) => {
+                slot.insert(choice);
+            }
+            btree_map::Entry::Occupied(mut slot) => {
+                if choice.items < slot.get().items {
+                    slot.insert(choice);
+                }
+            }
+        }
+    }
+
+    /// Knapsack over the tree: either the whole directory goes, or some of what's inside
+    fn frontier(
+        &self,
+        dir: &Dir,
+        path: &str,
+        target: u64,
+        work: &mut usize,
+    ) -> Result<Frontier, TooBig> {
+        let mut frontier = Frontier::default();
+        frontier.partial.insert(0, Choice::default());
+
+        for child in dir.children.values() {
+            let child_path = format!("{path}/{}", child.name());
+            let child_frontier = match child {
+                Node::Dir(child) => self.frontier(child, &child_path, target, work)?,
+                Node::File(file) if self.include_files => {
+                    let mut leaf = Frontier::default();
+                    leaf.partial.insert(0, Choice::default());
+                    self.add(&mut leaf, Choice::single(child_path, file.size), target);
+                    leaf
+                }
+                Node::File(_) => continue,
+            };
+
+            *work += frontier.partial.len() * child_frontier.partial.len();
+            if *work > self.max_work {
+                return Err(TooBig);
+            }
+
+            let mut merged = Frontier {
+                partial: BTreeMap::new(),
+                complete: frontier.complete.take(),
+            };
+            if let Some(complete) = child_frontier.complete {
+                self.keep_best(&mut merged.complete, complete);
+            }
+            for a in frontier.partial.values() {
+                for b in child_frontier.partial.values() {
+                    self.add(&mut merged, a.join(b), target);
+                }
+            }
+            frontier = merged;
+        }
+
+        let path = if path.is_empty() { "/" } else { path };
+        let whole = Choice::single(path.to_owned(), dir.size());
+        self.add(&mut frontier, whole, target);
+        Ok(frontier)
+    }
+
+    /// Best fit: take the smallest item that is enough if there's one, otherwise the biggest one,
+    /// and start again with what isn't inside or around it until enough is freed. The root is
+    /// only taken when the rest of the tree isn't enough.
+    fn greedy(&self, root: &Dir, target: u64) -> Choice {
+        let mut candidates = root
+            .entries()
+            .into_iter()
+            .filter(|entry| entry.depth > 0 && (entry.is_dir || self.include_files))
+            .collect::<Vec<Entry>>();
+        candidates.sort_by(|a, b| a.size.cmp(&b.size).then_with(|| a.path.cmp(&b.path)));
+
+        let mut choice = Choice::default();
+        while choice.bytes < target {
+            let remaining = target - choice.bytes;
+            let Some(next) = candidates
+                .iter()
+                .find(|entry| entry.size >= remaining)
+                .or_else(|| candidates.last())
+                .cloned()
+            else {
+                return Choice::single("/".to_owned(), root.size());
+            };
+
+            choice = choice.join(&Choice::single(next.path.clone(), next.size));
+            candidates.retain(|entry| {
+                !is_under(&entry.path, &next.path) && !is_under(&next.path, &entry.path)
+            });
+        }
+        choice
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::deletion::{DeletionPlanner, Objective};
+    use crate::{parse_transcript, read_tree, FreeSpace};
+
+    #[test]
+    pub fn test_deletion_plan() {
+        let root = read_tree("input_test.txt").unwrap();
+        // Part 2 only needs one directory
+        let plan = DeletionPlanner::default().plan(&root).unwrap();
+        assert_eq!(plan.paths, vec!["/d"]);
+        assert_eq!(plan.free_space, 46552477);
+        assert!(plan.exact);
+
+        // With files, c.dat is a tighter fit than d
+        let planner = DeletionPlanner {
+            include_files: true,
+            ..DeletionPlanner::default()
+        };
+        let plan = planner.plan(&root).unwrap();
+        assert_eq!(plan.paths, vec!["/c.dat"]);
+        assert_eq!(plan.freed, 8504156);
+    }
+
+    #[test]
+    pub fn test_objectives_and_fallback() {
+        let transcript = "$ cd /\n$ ls\ndir a\ndir b\ndir c\n$ cd a\n$ ls\n70 x\n$ cd ..\n\
+                          $ cd b\n$ ls\n30 x\n$ cd ..\n$ cd c\n$ ls\n25 x";
+        let root = parse_transcript(transcript).unwrap();
+        let bytes = DeletionPlanner {
+            free_space: FreeSpace {
+                disk_size: 170,
+                required: 100,
+            },
+            ..DeletionPlanner::default()
+        };
+
+        let plan = bytes.plan(&root).unwrap();
+        assert_eq!(plan.paths, vec!["/b", "/c"]);
+        assert_eq!((plan.freed, plan.free_space, plan.exact), (55, 100, true));
+
+        let items = DeletionPlanner {
+            objective: Objective::Items,
+            ..bytes
+        };
+        assert_eq!(items.plan(&root).unwrap().paths, vec!["/a"]);
+
+        let greedy = DeletionPlanner {
+            max_work: 0,
+            ..bytes
+        };
+        let plan = greedy.plan(&root).unwrap();
+        assert_eq!(plan.paths, vec!["/a"]);
+        assert!(!plan.exact);
+    }
+
+    #[test]
+    pub fn test_greedy_combines_dirs() {
+        let transcript = "$ cd /\n$ ls\ndir a\ndir b\ndir c\n$ cd a\n$ ls\n30 x\ndir d\n$ cd d\n\
+                          $ ls\n10 x\n$ cd /\n$ cd b\n$ ls\n30 x\n$ cd /\n$ cd c\n$ ls\n25 x";
+        let root = parse_transcript(transcript).unwrap();
+        let greedy = DeletionPlanner {
+            free_space: FreeSpace {
+                disk_size: 150,
+                required: 100,
+            },
+            max_work: 0,
+            ..DeletionPlanner::default()
+        };
+
+        // No directory but the root frees 45 on its own: the biggest goes, then the smallest
+        // that is enough and not inside it
+        let plan = greedy.plan(&root).unwrap();
+        assert_eq!(plan.paths, vec!["/a", "/c"]);
+        assert_eq!((plan.freed, plan.exact), (65, false));
+
+        // The files right under the root can only go with it
+        let root = parse_transcript("$ ls\n50 x\ndir a\n$ cd a\n$ ls\n10 y").unwrap();
+        let everything = DeletionPlanner {
+            free_space: FreeSpace {
+                disk_size: 100,
+                required: 100,
+            },
+            ..greedy
+        };
+        assert_eq!(everything.plan(&root).unwrap().paths, vec!["/"]);
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::VecDeque;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+use crate::{parse_transcript, Dir, Node};
+
+/// In which order the directories are listed
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Traversal {
+    #[default]
+    DepthFirst,
+    BreadthFirst,
+}
+
+/// In which order the children of a directory are visited and listed
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Order {
+    #[default]
+    Sorted,
+    Reversed,
+    Shuffled,
+}
+
+/// Writes the terminal transcript of someone exploring a filesystem, the input of the puzzle
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Generator {
+    pub traversal: Traversal,
+    pub order: Order,
+    /// Sometimes go back to the root with `cd /`, or higher than needed, before going down again
+    pub random_cd: bool,
+    /// Chance, in percent, to list a directory again. Anything above 99 counts as 99, the
+    /// listing would never end otherwise.
+    pub repeat_ls: u32,
+    pub seed: u64,
+}
+
+/// Small xorshift generator, good enough to shuffle things around
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        // xorshift never leaves 0, so the state must be odd to start with
+        Rng((seed ^ 0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn chance(&mut self, percent: u32) -> bool {
+        self.next() % 100 < percent as u64
+    }
+}
+
+impl Generator {
+    pub fn transcript(&self, root: &Dir) -> String {
+        let mut rng = Rng::new(self.seed);
+        let mut lines = vec!["$ cd /".to_owned()];
+        let mut cwd: Vec<String> = vec![];
+
+        for path in self.visit_order(root, &mut rng) {
+            self.navigate(&mut cwd, &path, &mut rng, &mut lines);
+            let dir = root.dir(&path).expect("visited directories exist");
+            loop {
+                lines.push("$ ls".to_owned());
+                for child in self.children(dir, &mut rng) {
+                    lines.push(match child {
+                        Node::Dir(child) => format!("dir {}", child.name),
+                        Node::File(file) => format!("{} {}", file.size, file.name),
+                    });
+                }
+                if !rng.chance(self.repeat_ls.min(99)) {
+                    break;
+                }
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Paths of the directories to list, the root first
+    fn visit_order(&self, root: &Dir, rng: &mut Rng) -> Vec<Vec<String>> {
+        let mut order = vec![];
+        let mut pending = VecDeque::from([vec![]]);
+        while let Some(path) = pending.pop_front() {
+            let dir = root.dir(&path).expect("visited directories exist");
+            let children = self
+                .children(dir, rng)
+                .into_iter()
+                .filter_map(|child| match child {
+                    Node::Dir(child) => {
+                        let mut child_path = path.clone();
+                        child_path.push(child.name.clone());
+                        Some(child_path)
+                    }
+                    Node::File(_) => None,
+                })
+                .collect::<Vec<_>>();
+            match self.traversal {
+                Traversal::BreadthFirst => pending.extend(children),
+                Traversal::DepthFirst => {
+                    for child in children.into_iter().rev() {
+                        pending.push_front(child);
+                    }
+                }
+            }
+            order.push(path);
+        }
+        order
+    }
+
+    fn children<'a>(&self, dir: &'a Dir, rng: &mut Rng) -> Vec<&'a Node> {
+        let mut children = dir.children.values().collect::<Vec<&Node>>();
+        match self.order {
+            Order::Sorted => {}
+            Order::Reversed => children.reverse(),
+            Order::Shuffled => {
+                for i in (1..children.len()).rev() {
+                    children.swap(i, rng.below(i + 1));
+                }
+            }
+        }
+        children
+    }
+
+    /// Write the `cd` commands going from `cwd` to `target`
+    fn navigate(
+        &self,
+        cwd: &mut Vec<String>,
+        target: &[String],
+        rng: &mut Rng,
+        lines: &mut Vec<String>,
+    ) {
+        let mut common = cwd.iter().zip(target).take_while(|(a, b)| a == b).count();
+        if self.random_cd && rng.chance(30) {
+            if rng.chance(50) {
+                lines.push("$ cd /".to_owned());
+                cwd.clear();
+                common = 0;
+            } else {
+                // Go up one more level than needed
+                common = common.saturating_sub(1);
+            }
+        }
+
+        while cwd.len() > common {
+            lines.push("$ cd ..".to_owned());
+            cwd.pop();
+        }
+        for name in &target[common..] {
+            lines.push(format!("$ cd {name}"));
+            cwd.push(name.clone());
+        }
+    }
+
+    /// Write the transcript of a tree, read it back, and check we get the same files and sizes
+    pub fn check(&self, root: &Dir) -> anyhow::Result<()> {
+        let parsed = parse_transcript(&self.transcript(root))?;
+        let expected = root.entries();
+        let found = parsed.entries();
+        for (expected, found) in expected.iter().zip(&found) {
+            if expected != found {
+                bail!("Expected {expected:?}, found {found:?}");
+            }
+        }
+        if expected.len() != found.len() {
+            bail!(
+                "Expected {} files and directories, found {}",
+                expected.len(),
+                found.len()
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Read a directory on disk. Symbolic links are not followed, and names with spaces are left out
+/// since the transcript can't hold them.
+pub fn from_disk(path: &Path) -> anyhow::Result<Dir> {
+    let mut root = Dir::new("/");
+    read_disk(path, &mut root)?;
+    Ok(root)
+}
+
+fn read_disk(path: &Path, dir: &mut Dir) -> anyhow::Result<()> {
+    for entry in std::fs::read_dir(path).with_context(|| format!("Can't read {path:?}"))? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if name.contains(char::is_whitespace) {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            read_disk(&entry.path(), dir.add_dir(&name)?)?;
+        } else if file_type.is_file() {
+            dir.add_file(&name, entry.metadata()?.len())?;
+        }
+    }
+    Ok(())
+}
+
+/// A random tree with the given number of directories and files
+pub fn synthetic(seed: u64, dirs: usize, files: usize) -> Dir {
+    let mut rng = Rng::new(seed);
+    let mut root = Dir::new("/");
+    let mut paths: Vec<Vec<String>> = vec![vec![]];
+    let name = |rng: &mut Rng, index: usize| {
+        let letters = (0..1 + rng.below(6))
+            .map(|_| (b'a' + rng.below(26) as u8) as char)
+            .collect::<String>();
+        format!("{letters}{index}")
+    };
+
+    for index in 0..dirs {
+        let mut path = paths[rng.below(paths.len())].clone();
+        let dir_name = name(&mut rng, index);
+        root.dir_mut(&path)
+            .expect("generated directories exist")
+            .add_dir(&dir_name)
+            .expect("directory names are never file names");
+        path.push(dir_name);
+        paths.push(path);
+    }
+    for index in 0..files {
+        let path = &paths[rng.below(paths.len())];
+        // Directories have no extension, so a file never takes the name of one
+        let extension = ["txt", "dat", "log", "lst"][rng.below(4)];
+        let file_name = format!("{}.{extension}", name(&mut rng, index));
+        let size = 1 + rng.below(300000) as u64;
+        root.dir_mut(path)
+            .expect("generated directories exist")
+            .add_file(&file_name, size)
+            .expect("file names are never directory names");
+    }
+    root
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::generator::{from_disk, synthetic, Generator, Order, Traversal};
+    use crate::{read_tree, size_of};
+
+    #[test]
+    pub fn test_round_trip() {
+        let trees = [read_tree("input_test.txt").unwrap(), synthetic(7, 40, 200)];
+        for root in &trees {
+            for traversal in [Traversal::DepthFirst, Traversal::BreadthFirst] {
+                for order in [Order::Sorted, Order::Reversed, Order::Shuffled] {
+                    let generator = Generator {
+                        traversal,
+                        order,
+                        random_cd: true,
+                        repeat_ls: 20,
+                        seed: 42,
+                    };
+                    generator.check(root).unwrap();
+                }
+            }
+        }
+
+        // The puzzle's own transcript is a plain depth first one
+        let root = read_tree("input_test.txt").unwrap();
+        let transcript = Generator::default().transcript(&root);
+        assert!(transcript.starts_with("$ cd /\n$ ls\ndir a\n14848514 b.txt"));
+        assert!(transcript.contains("$ cd e\n$ ls\n584 i\n$ cd ..\n$ cd ..\n$ cd d"));
+    }
+
+    #[test]
+    pub fn test_transcript_ends() {
+        let root = read_tree("input_test.txt").unwrap();
+        let always = Generator {
+            repeat_ls: 100,
+            ..Generator::default()
+        };
+        always.check(&root).unwrap();
+
+        // This seed used to start the generator at 0, where it stays forever
+        let unlucky = Generator {
+            repeat_ls: 50,
+            seed: 0x9E37_79B9_7F4A_7C15,
+            ..Generator::default()
+        };
+        unlucky.check(&root).unwrap();
+    }
+
+    #[test]
+    pub fn test_from_disk() {
+        let base = std::env::temp_dir().join(format!("aoc_7_{}", std::process::id()));
+        std::fs::create_dir_all(base.join("a/b")).unwrap();
+        std::fs::write(base.join("a/b/c.txt"), [0; 100]).unwrap();
+        std::fs::write(base.join("a/d"), [0; 20]).unwrap();
+        std::fs::write(base.join("e f"), [0; 5]).unwrap();
+
+        let root = from_disk(&base);
+        std::fs::remove_dir_all(&base).unwrap();
+        let root = root.unwrap();
+
+        assert_eq!(size_of(&root, "/").unwrap(), 120);
+        assert_eq!(size_of(&root, "/a/b").unwrap(), 100);
+        Generator::default().check(&root).unwrap();
+    }
+}
@@ -1,89 +1,147 @@
-use std::collections::HashMap;
+use anyhow::{bail, Context};
+
+pub use crate::deletion::{Deletion, DeletionPlanner, Objective};
+pub use crate::generator::{from_disk, synthetic, Generator, Order, Traversal};
+pub use crate::query::{glob_match, size_of, FreeSpace, Kind, Query};
+pub use crate::render::{human_size, render, Format};
+pub use crate::tree::{Dir, Entry, File, Node};
+
+mod deletion;
+mod generator;
+mod query;
+mod render;
+mod tree;
 
 #[derive(Debug)]
-enum Entry {
+enum Line {
     Cd(String),
     GoUp,
-    File(u32, String),
+    Root,
+    Ls,
+    Dir(String),
+    File(u64, String),
 }
 
-pub fn part1(input_path: &str) -> Option<u32> {
-    let map = my_own_du(input_path)?;
+pub fn part1(input_path: &str) -> anyhow::Result<u64> {
+    let tree = read_tree(input_path)?;
 
-    Some(
-        map.iter()
-            .filter(|(_, y)| **y <= 100000)
-            .map(|(_, y)| y)
-            .sum(),
-    )
+    let query = Query {
+        kind: Kind::Dir,
+        max_size: Some(100000),
+        ..Query::default()
+    };
+    Ok(query.run(&tree).iter().map(|dir| dir.size).sum())
 }
 
-pub fn part2(input_path: &str) -> Option<u32> {
-    let map = my_own_du(input_path)?;
+pub fn part2(input_path: &str) -> anyhow::Result<u64> {
+    let tree = read_tree(input_path)?;
 
-    let total_size = 70000000;
-    let needed_space = 30000000;
-    let unused_space = total_size - map.get("/").unwrap();
-    let space_to_clean = needed_space - unused_space;
+    let dir = FreeSpace::default()
+        .smallest_dir(&tree)?
+        .context("There is already enough space")?;
+    Ok(dir.size)
+}
 
-    map.iter()
-        .filter_map(|(_, y)| if *y >= space_to_clean { Some(*y) } else { None })
-        .min()
+/// Read the transcript and rebuild the filesystem
+pub fn read_tree(input_path: &str) -> anyhow::Result<Dir> {
+    parse_transcript(&std::fs::read_to_string(input_path)?)
 }
 
-fn my_own_du(input_path: &str) -> Option<HashMap<String, u32>> {
-    let mut map = HashMap::<String, u32>::new();
-    let mut directory_path: Vec<String> = vec![];
-
-    std::fs::read_to_string(input_path)
-        .ok()?
-        .lines()
-        .filter_map(parse_line)
-        .for_each(|entry| match entry {
-            Entry::File(file_size, _file_name) => {
-                for directory in &directory_path {
-                    *map.entry(directory.clone()).or_default() += file_size;
-                }
+/// Rebuild the filesystem from the commands and their output
+pub fn parse_transcript(transcript: &str) -> anyhow::Result<Dir> {
+    let mut root = Dir::new("/");
+    // Path of the current directory, from the root
+    let mut cwd: Vec<String> = vec![];
+
+    for (index, line) in transcript.lines().enumerate() {
+        let context = || format!("Line {}: {line:?}", index + 1);
+        let current = root
+            .dir_mut(&cwd)
+            .with_context(|| format!("{} is not a directory", cwd.join("/")))
+            .with_context(context)?;
+
+        match parse_line(line).with_context(context)? {
+            Line::Root => cwd.clear(),
+            Line::GoUp => {
+                // Like a shell, going up from the root stays at the root
+                cwd.pop();
             }
-            Entry::Cd(change_dir) => {
-                let mut complete_path = directory_path.join("/");
-                complete_path.push_str(&change_dir);
-                directory_path.push(complete_path);
+            Line::Cd(name) => {
+                // Directories are usually listed before we go in, but there's no need to
+                current.add_dir(&name).with_context(context)?;
+                cwd.push(name);
             }
-            Entry::GoUp => {
-                directory_path.pop().unwrap();
+            Line::Ls => {}
+            Line::Dir(name) => {
+                current.add_dir(&name).with_context(context)?;
             }
-        });
+            Line::File(size, name) => current.add_file(&name, size).with_context(context)?,
+        }
+    }
 
-    Some(map)
+    Ok(root)
 }
 
-fn parse_line(line: &str) -> Option<Entry> {
-    match line {
-        x if x.starts_with("$ cd ..") => Some(Entry::GoUp),
-        x if x.starts_with("$ cd ") => {
-            let directory_name = x.split_whitespace().collect::<Vec<&str>>()[2];
-            Some(Entry::Cd(directory_name.to_owned()))
-        }
-        x if x.starts_with(char::is_numeric) => {
-            let input = x.split_whitespace().collect::<Vec<&str>>();
-            Some(Entry::File(input[0].parse().unwrap(), input[1].to_owned()))
-        }
-        _ => None,
-    }
+fn parse_line(line: &str) -> anyhow::Result<Line> {
+    let words = line.split_whitespace().collect::<Vec<&str>>();
+    Ok(match words[..] {
+        ["$", "cd", "/"] => Line::Root,
+        ["$", "cd", ".."] => Line::GoUp,
+        ["$", "cd", name] => Line::Cd(name.to_owned()),
+        ["$", "ls"] => Line::Ls,
+        ["dir", name] => Line::Dir(name.to_owned()),
+        [size, name] => Line::File(
+            size.parse()
+                .with_context(|| format!("Invalid size {size}"))?,
+            name.to_owned(),
+        ),
+        _ => bail!("Unknown command or output"),
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{part1, part2};
+    use crate::{parse_transcript, part1, part2};
 
     #[test]
     pub fn test_part1() {
-        assert_eq!(part1("input_test.txt"), Some(95437))
+        assert_eq!(part1("input_test.txt").unwrap(), 95437)
     }
 
     #[test]
     pub fn test_part2() {
-        assert_eq!(part2("input_test.txt"), Some(24933642))
+        assert_eq!(part2("input_test.txt").unwrap(), 24933642)
+    }
+
+    #[test]
+    pub fn test_transcript() {
+        let transcript = "$ cd /\n$ ls\ndir a\n1 b\n$ cd a\n$ ls\n2 c\n$ cd /\n$ ls\ndir a\n1 b\n\
+                          $ cd a\n$ ls\n2 c\n$ cd ..\n$ cd ..\n$ cd d\n$ ls\n4000000000 e";
+        let root = parse_transcript(transcript).unwrap();
+
+        // Listing again doesn't count twice, `cd /` goes back to the root
+        assert_eq!(root.dir(&["a"]).unwrap().size(), 2);
+        assert_eq!(root.dir(&["d"]).unwrap().size(), 4000000000);
+        assert_eq!(root.size(), 4000000003);
+
+        let error = parse_transcript("$ cd /\n$ ls\nabc b").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 3: \"abc b\": Invalid size abc: invalid digit found in string"
+        );
+
+        let error = parse_transcript("$ cd /\n$ ls\n10 a\n$ cd a").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 4: \"$ cd a\": a is already listed as a file"
+        );
+        assert!(parse_transcript("$ ls\n10 a\ndir a").is_err());
+        let error =
+            parse_transcript("$ cd /\n$ ls\ndir a\n$ cd a\n$ ls\n500 x\n$ cd ..\n$ ls\n10 a")
+                .unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 9: \"10 a\": a is already listed as a directory"
+        );
     }
 }
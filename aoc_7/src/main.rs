@@ -1,11 +1,169 @@
-use aoc_7::{part1, part2};
+use anyhow::{bail, Context};
+use aoc_7::{
+    from_disk, human_size, part1, part2, read_tree, render, size_of, synthetic, DeletionPlanner,
+    Format, FreeSpace, Generator, Objective, Order, Query, Traversal,
+};
+
+/// Print the filesystem of the input in the given format
+fn print_tree(format: &str, max_depth: Option<usize>) -> anyhow::Result<()> {
+    let format = format.parse::<Format>()?;
+    let tree = read_tree("input.txt")?;
+    print!("{}", render(&tree, format, max_depth));
+    Ok(())
+}
+
+/// Run a query, or the `size` and `free-space` commands, on the filesystem of the input
+fn query(command: &str, args: &[String]) -> anyhow::Result<()> {
+    let tree = read_tree("input.txt")?;
+    match command {
+        "query" => {
+            for entry in Query::from_args(args)?.run(&tree) {
+                let kind = if entry.is_dir { "dir" } else { "file" };
+                println!("{:>10} {kind:<4} {}", entry.size, entry.path);
+            }
+        }
+        "size" => {
+            let path = args.first().map_or("/", String::as_str);
+            println!("{}", size_of(&tree, path)?);
+        }
+        _ => {
+            // `free-space [disk size] [required]`, the puzzle's device by default
+            let mut free_space = FreeSpace::default();
+            if let Some(disk_size) = args.first() {
+                free_space.disk_size = disk_size.parse()?;
+            }
+            if let Some(required) = args.get(1) {
+                free_space.required = required.parse()?;
+            }
+            println!(
+                "Used {}, unused {}, to free {}",
+                human_size(tree.size()),
+                human_size(free_space.unused(&tree)),
+                human_size(free_space.to_free(&tree))
+            );
+            match free_space.smallest_dir(&tree)? {
+                Some(dir) => println!("Delete {} ({})", dir.path, dir.size),
+                None => println!("There is already enough space"),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// `delete [--files] [--items] [disk size] [required]`, the puzzle's device by default
+fn delete(args: &[String]) -> anyhow::Result<()> {
+    let tree = read_tree("input.txt")?;
+    let mut planner = DeletionPlanner::default();
+    let mut sizes = vec![];
+    for arg in args {
+        match arg.as_str() {
+            "--files" => planner.include_files = true,
+            "--items" => planner.objective = Objective::Items,
+            size => sizes.push(size.parse()?),
+        }
+    }
+    if let Some(disk_size) = sizes.first() {
+        planner.free_space.disk_size = *disk_size;
+    }
+    if let Some(required) = sizes.get(1) {
+        planner.free_space.required = *required;
+    }
+
+    let deletion = planner.plan(&tree)?;
+    for path in &deletion.paths {
+        println!("{path}");
+    }
+    println!(
+        "Freed {}, free space {}{}",
+        deletion.freed,
+        deletion.free_space,
+        if deletion.exact { "" } else { " (best guess)" }
+    );
+    Ok(())
+}
+
+/// `generate [PATH|synthetic] [--bfs] [--reverse|--shuffle] [--random-cd] [--repeat-ls N]
+/// [--seed N] [--check]`: print the transcript of a directory, or check it reads back the same
+fn generate(args: &[String]) -> anyhow::Result<()> {
+    let mut generator = Generator::default();
+    let mut source = None;
+    let mut check = false;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--bfs" => generator.traversal = Traversal::BreadthFirst,
+            "--reverse" => generator.order = Order::Reversed,
+            "--shuffle" => generator.order = Order::Shuffled,
+            "--random-cd" => generator.random_cd = true,
+            "--repeat-ls" => {
+                generator.repeat_ls = args.next().context("Missing chance")?.parse()?;
+                if generator.repeat_ls > 99 {
+                    bail!("The chance to list again must be below 100, it would never stop");
+                }
+            }
+            "--seed" => generator.seed = args.next().context("Missing seed")?.parse()?,
+            "--check" => check = true,
+            path => source = Some(path),
+        }
+    }
+
+    let tree = match source {
+        None | Some("synthetic") => synthetic(generator.seed, 50, 300),
+        Some(path) => from_disk(std::path::Path::new(path))?,
+    };
+    if check {
+        generator.check(&tree)?;
+        println!(
+            "{} files and directories, {} bytes, the transcript reads back the same",
+            tree.entries().len(),
+            tree.size()
+        );
+    } else {
+        println!("{}", generator.transcript(&tree));
+    }
+    Ok(())
+}
 
 fn main() {
-    if let Some(u) = part1("input.txt") {
-        println!("Part 1: {u}");
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("render") {
+        // `render [puzzle|tree|du] [depth]`
+        let format = args.get(2).map_or("puzzle", String::as_str);
+        let max_depth = args.get(3).and_then(|x| x.parse().ok());
+        if let Err(e) = print_tree(format, max_depth) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
+    if let Some(command @ ("query" | "size" | "free-space")) = args.get(1).map(String::as_str) {
+        if let Err(e) = query(command, &args[2..]) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("delete") {
+        if let Err(e) = delete(&args[2..]) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
+    if args.get(1).map(String::as_str) == Some("generate") {
+        if let Err(e) = generate(&args[2..]) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+
+    match part1("input.txt") {
+        Ok(u) => println!("Part 1: {u}"),
+        Err(e) => eprintln!("{e:#}"),
     }
 
-    if let Some(u) = part2("input.txt") {
-        println!("Part 2: {u}");
+    match part2("input.txt") {
+        Ok(u) => println!("Part 2: {u}"),
+        Err(e) => eprintln!("{e:#}"),
     }
 }
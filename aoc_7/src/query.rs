@@ -0,0 +1,295 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+use crate::{Dir, Entry};
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub enum Kind {
+    #[default]
+    Any,
+    File,
+    Dir,
+}
+
+impl FromStr for Kind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "any" => Kind::Any,
+            "f" | "file" => Kind::File,
+            "d" | "dir" => Kind::Dir,
+            _ => bail!("Unknown type {s}, expected f, d or any"),
+        })
+    }
+}
+
+/// Look for files and directories, every criterion has to match
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Query {
+    pub kind: Kind,
+    /// Glob on the name, or on the absolute path if it has a `/`. `*` matches anything, `?` a
+    /// single character.
+    pub glob: Option<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+    /// Only look under this directory
+    pub under: Option<String>,
+    /// Only keep the biggest ones
+    pub top: Option<usize>,
+}
+
+impl Query {
+    /// Read a query from the command line, like
+    /// `--type d --name '*.txt' --min 10 --max 1000 --depth 2 --under /a --top 3`
+    pub fn from_args(args: &[String]) -> anyhow::Result<Self> {
+        let mut query = Query::default();
+        let mut args = args.iter();
+        while let Some(option) = args.next() {
+            let value = args
+                .next()
+                .with_context(|| format!("Missing value for {option}"))?;
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .with_context(|| format!("Invalid number {value} for {option}"))
+            };
+            match option.as_str() {
+                "--type" => query.kind = value.parse()?,
+                "--name" => query.glob = Some(value.clone()),
+                "--min" => query.min_size = Some(number()?),
+                "--max" => query.max_size = Some(number()?),
+                "--min-depth" => query.min_depth = Some(number()? as usize),
+                "--max-depth" => query.max_depth = Some(number()? as usize),
+                "--depth" => {
+                    query.min_depth = Some(number()? as usize);
+                    query.max_depth = query.min_depth;
+                }
+                "--under" => query.under = Some(value.clone()),
+                "--top" => query.top = Some(number()? as usize),
+                _ => bail!("Unknown option {option}"),
+            }
+        }
+        Ok(query)
+    }
+
+    pub fn matches(&self, entry: &Entry) -> bool {
+        let kind = match self.kind {
+            Kind::Any => true,
+            Kind::File => !entry.is_dir,
+            Kind::Dir => entry.is_dir,
+        };
+        let glob = self.glob.as_ref().is_none_or(|glob| {
+            if glob.contains('/') {
+                glob_match(glob, &entry.path)
+            } else {
+                glob_match(glob, entry.name())
+            }
+        });
+        let under = self
+            .under
+            .as_ref()
+            .is_none_or(|under| is_under(&entry.path, under));
+
+        kind && glob
+            && under
+            && self.min_size.is_none_or(|min| entry.size >= min)
+            && self.max_size.is_none_or(|max| entry.size <= max)
+            && self.min_depth.is_none_or(|min| entry.depth >= min)
+            && self.max_depth.is_none_or(|max| entry.depth <= max)
+    }
+
+    /// Every matching entry, in the order of the tree, or from the biggest if `top` is set
+    pub fn run(&self, root: &Dir) -> Vec<Entry> {
+        let mut entries = root
+            .entries()
+            .into_iter()
+            .filter(|entry| self.matches(entry))
+            .collect::<Vec<Entry>>();
+        if let Some(top) = self.top {
+            entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+            entries.truncate(top);
+        }
+        entries
+    }
+}
+
+/// Total size of the file or directory at the given absolute path
+pub fn size_of(root: &Dir, path: &str) -> anyhow::Result<u64> {
+    let path = normalize(path);
+    root.entries()
+        .into_iter()
+        .find(|entry| entry.path == path)
+        .map(|entry| entry.size)
+        .with_context(|| format!("No such file or directory {path}"))
+}
+
+/// How much space a device needs, and how to get it back
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FreeSpace {
+    pub disk_size: u64,
+    /// Free space needed on the disk
+    pub required: u64,
+}
+
+/// The device of the puzzle
+impl Default for FreeSpace {
+    fn default() -> Self {
+        FreeSpace {
+            disk_size: 70000000,
+            required: 30000000,
+        }
+    }
+}
+
+impl FreeSpace {
+    /// Space left on the disk
+    pub fn unused(&self, root: &Dir) -> u64 {
+        self.disk_size.saturating_sub(root.size())
+    }
+
+    /// How much has to be deleted, 0 if there is already enough space
+    pub fn to_free(&self, root: &Dir) -> u64 {
+        self.required.saturating_sub(self.unused(root))
+    }
+
+    /// The smallest directory to delete to get enough space, `None` if there is already enough
+    pub fn smallest_dir(&self, root: &Dir) -> anyhow::Result<Option<Entry>> {
+        if self.required > self.disk_size {
+            bail!(
+                "The disk is {} bytes, {} can't be freed",
+                self.disk_size,
+                self.required
+            );
+        }
+        let to_free = self.to_free(root);
+        if to_free == 0 {
+            return Ok(None);
+        }
+
+        root.dirs()
+            .filter(|dir| dir.size >= to_free)
+            .min_by_key(|dir| dir.size)
+            .map(Some)
+            .with_context(|| format!("No directory is big enough to free {to_free} bytes"))
+    }
+}
+
+/// `/a/b/` and `a/b` are both `/a/b`
+fn normalize(path: &str) -> String {
+    format!("/{}", path.trim_matches('/'))
+}
+
+/// Whether `path` is `dir` or inside it
+pub(crate) fn is_under(path: &str, dir: &str) -> bool {
+    let dir = normalize(dir);
+    dir == "/" || path == dir || path.strip_prefix(&dir).is_some_and(|p| p.starts_with('/'))
+}
+
+/// Match `text` against a glob where `*` matches any sequence of characters and `?` exactly one
+pub fn glob_match(glob: &str, text: &str) -> bool {
+    let glob = glob.chars().collect::<Vec<char>>();
+    let text = text.chars().collect::<Vec<char>>();
+    // Position in the glob and the text of the last `*`, to backtrack to
+    let mut star = None;
+    let (mut g, mut t) = (0, 0);
+
+    while t < text.len() {
+        match glob.get(g) {
+            Some('*') => {
+                star = Some((g, t));
+                g += 1;
+            }
+            Some(c) if *c == '?' || *c == text[t] => {
+                g += 1;
+                t += 1;
+            }
+            _ => match star {
+                // Let the last `*` eat one more character
+                Some((star_g, star_t)) => {
+                    star = Some((star_g, star_t + 1));
+                    g = star_g + 1;
+                    t = star_t + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    glob[g..].iter().all(|c| *c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::{glob_match, size_of, FreeSpace, Kind, Query};
+    use crate::read_tree;
+
+    #[test]
+    pub fn test_glob() {
+        assert!(glob_match("*.txt", "b.txt"));
+        assert!(!glob_match("*.txt", "b.txt.gz"));
+        assert!(glob_match("d.???", "d.log"));
+        assert!(glob_match("*", ""));
+        assert!(glob_match("/a/*/i", "/a/e/i"));
+        assert!(glob_match("a*b*c", "aXbYbZc"));
+        assert!(!glob_match("a*b", "aXbYc"));
+    }
+
+    #[test]
+    pub fn test_queries() {
+        let root = read_tree("input_test.txt").unwrap();
+        let paths = |query: &Query| {
+            query
+                .run(&root)
+                .into_iter()
+                .map(|entry| entry.path)
+                .collect::<Vec<String>>()
+        };
+
+        let query = Query {
+            kind: Kind::File,
+            glob: Some("d.*".to_owned()),
+            ..Query::default()
+        };
+        assert_eq!(paths(&query), vec!["/d/d.ext", "/d/d.log"]);
+
+        let args = "--type d --max 100000"
+            .split(' ')
+            .map(str::to_owned)
+            .collect::<Vec<String>>();
+        assert_eq!(paths(&Query::from_args(&args).unwrap()), vec!["/a", "/a/e"]);
+
+        let query = Query {
+            under: Some("/a".to_owned()),
+            min_depth: Some(2),
+            top: Some(2),
+            ..Query::default()
+        };
+        assert_eq!(paths(&query), vec!["/a/h.lst", "/a/f"]);
+
+        assert_eq!(size_of(&root, "/a/").unwrap(), 94853);
+        assert!(size_of(&root, "/x").is_err());
+    }
+
+    #[test]
+    pub fn test_free_space() {
+        let root = read_tree("input_test.txt").unwrap();
+        let dir = FreeSpace::default().smallest_dir(&root).unwrap().unwrap();
+        assert_eq!((dir.path.as_str(), dir.size), ("/d", 24933642));
+
+        let free_space = FreeSpace {
+            disk_size: 100000000,
+            required: 90000000,
+        };
+        assert_eq!(free_space.to_free(&root), 38381165);
+        assert_eq!(free_space.smallest_dir(&root).unwrap().unwrap().path, "/");
+
+        let free_space = FreeSpace {
+            disk_size: 100000000,
+            required: 10000000,
+        };
+        assert_eq!(free_space.smallest_dir(&root).unwrap(), None);
+    }
+}
@@ -0,0 +1,189 @@
+use std::str::FromStr;
+
+use anyhow::bail;
+
+use crate::{Dir, Node};
+
+/// How to print the filesystem
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Format {
+    /// Like the puzzle does, `- / (dir)` and `- b.txt (file, size=14848514)`
+    Puzzle,
+    /// Like `tree`, with the total size of each directory
+    Tree,
+    /// Like `du -h`, the directories from the biggest to the smallest
+    Du,
+}
+
+impl FromStr for Format {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "puzzle" => Format::Puzzle,
+            "tree" => Format::Tree,
+            "du" => Format::Du,
+            _ => bail!("Unknown format {s}, expected puzzle, tree or du"),
+        })
+    }
+}
+
+/// Print the filesystem, down to `max_depth` levels under the root if given
+pub fn render(root: &Dir, format: Format, max_depth: Option<usize>) -> String {
+    let max_depth = max_depth.unwrap_or(usize::MAX);
+    let mut output = String::new();
+    match format {
+        Format::Puzzle => render_puzzle(root, 0, max_depth, &mut output),
+        Format::Tree => {
+            output.push_str(&format!("{} ({})\n", root.name, root.size()));
+            render_tree(root, "", 1, max_depth, &mut output);
+        }
+        Format::Du => render_du(root, max_depth, &mut output),
+    }
+    output
+}
+
+fn render_puzzle(dir: &Dir, depth: usize, max_depth: usize, output: &mut String) {
+    let indent = "  ".repeat(depth);
+    output.push_str(&format!("{indent}- {} (dir)\n", dir.name));
+    if depth == max_depth {
+        return;
+    }
+
+    for child in dir.children.values() {
+        match child {
+            Node::Dir(child) => render_puzzle(child, depth + 1, max_depth, output),
+            Node::File(file) => output.push_str(&format!(
+                "{indent}  - {} (file, size={})\n",
+                file.name, file.size
+            )),
+        }
+    }
+}
+
+fn render_tree(dir: &Dir, prefix: &str, depth: usize, max_depth: usize, output: &mut String) {
+    if depth > max_depth {
+        return;
+    }
+
+    let count = dir.children.len();
+    for (index, child) in dir.children.values().enumerate() {
+        let last = index + 1 == count;
+        let branch = if last { "└── " } else { "├── " };
+        output.push_str(&format!(
+            "{prefix}{branch}{} ({})\n",
+            child.name(),
+            child.size()
+        ));
+
+        if let Node::Dir(child) = child {
+            let prefix = format!("{prefix}{}", if last { "    " } else { "│   " });
+            render_tree(child, &prefix, depth + 1, max_depth, output);
+        }
+    }
+}
+
+fn render_du(root: &Dir, max_depth: usize, output: &mut String) {
+    let mut dirs = root
+        .dirs()
+        .filter(|dir| dir.depth <= max_depth)
+        .collect::<Vec<_>>();
+    // Biggest first, then by path to keep the output stable
+    dirs.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+
+    for dir in dirs {
+        output.push_str(&format!("{:>5}\t{}\n", human_size(dir.size), dir.path));
+    }
+}
+
+/// Size with a binary unit the way `du -h` writes it: a decimal under 10, rounded up
+pub fn human_size(size: u64) -> String {
+    const UNITS: [&str; 6] = ["K", "M", "G", "T", "P", "E"];
+
+    if size < 1024 {
+        return size.to_string();
+    }
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    if value < 10.0 {
+        let rounded = (value * 10.0).ceil() / 10.0;
+        if rounded < 10.0 {
+            return format!("{rounded:.1}{}", UNITS[unit]);
+        }
+    }
+    let rounded = value.ceil();
+    if rounded >= 1024.0 && unit + 1 < UNITS.len() {
+        format!("1.0{}", UNITS[unit + 1])
+    } else {
+        format!("{rounded}{}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::read_tree;
+    use crate::render::{human_size, render, Format};
+
+    #[test]
+    pub fn test_human_size() {
+        assert_eq!(human_size(584), "584");
+        assert_eq!(human_size(1024), "1.0K");
+        assert_eq!(human_size(1025), "1.1K");
+        assert_eq!(human_size(94853), "93K");
+        assert_eq!(human_size(10239), "10K");
+        assert_eq!(human_size(48381165), "47M");
+        assert_eq!(human_size(1024 * 1024 - 1), "1.0M");
+    }
+
+    #[test]
+    pub fn test_render() {
+        let root = read_tree("input_test.txt").unwrap();
+
+        assert_eq!(
+            render(&root, Format::Puzzle, None),
+            "- / (dir)
+  - a (dir)
+    - e (dir)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+
+        assert_eq!(
+            render(&root, Format::Tree, Some(2)),
+            "/ (48381165)
+├── a (94853)
+│   ├── e (584)
+│   ├── f (29116)
+│   ├── g (2557)
+│   └── h.lst (62596)
+├── b.txt (14848514)
+├── c.dat (8504156)
+└── d (24933642)
+    ├── d.ext (5626152)
+    ├── d.log (8033020)
+    ├── j (4060174)
+    └── k (7214296)
+"
+        );
+
+        assert_eq!(
+            render(&root, Format::Du, Some(1)),
+            "  47M\t/\n  24M\t/d\n  93K\t/a\n"
+        );
+    }
+}
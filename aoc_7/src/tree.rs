@@ -0,0 +1,205 @@
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct File {
+    pub name: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Dir {
+    pub name: String,
+    /// Children by name, so listing a directory twice doesn't add anything
+    pub children: BTreeMap<String, Node>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Node {
+    Dir(Dir),
+    File(File),
+}
+
+impl Node {
+    pub fn name(&self) -> &str {
+        match self {
+            Node::Dir(dir) => &dir.name,
+            Node::File(file) => &file.name,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        match self {
+            Node::Dir(dir) => dir.size(),
+            Node::File(file) => file.size,
+        }
+    }
+}
+
+/// A file or a directory of the tree, with its total size already computed
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Entry {
+    /// Absolute path, `/` for the root directory
+    pub path: String,
+    /// Number of directories between the root and the entry, 0 for the root
+    pub depth: usize,
+    /// Size of the file, or of everything under the directory
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+impl Entry {
+    /// Last part of the path, `/` for the root directory
+    pub fn name(&self) -> &str {
+        match self.path.rsplit_once('/') {
+            Some((_, "")) | None => "/",
+            Some((_, name)) => name,
+        }
+    }
+}
+
+impl Dir {
+    pub fn new(name: &str) -> Self {
+        Dir {
+            name: name.to_owned(),
+            children: BTreeMap::new(),
+        }
+    }
+
+    /// Size of all the files under this directory
+    pub fn size(&self) -> u64 {
+        self.children.values().map(Node::size).sum()
+    }
+
+    /// The directory at the given path, relative to this one
+    pub fn dir<S: AsRef<str>>(&self, path: &[S]) -> Option<&Dir> {
+        path.iter()
+            .try_fold(self, |dir, name| match dir.children.get(name.as_ref()) {
+                Some(Node::Dir(child)) => Some(child),
+                _ => None,
+            })
+    }
+
+    pub fn dir_mut<S: AsRef<str>>(&mut self, path: &[S]) -> Option<&mut Dir> {
+        path.iter().try_fold(self, |dir, name| {
+            match dir.children.get_mut(name.as_ref()) {
+                Some(Node::Dir(child)) => Some(child),
+                _ => None,
+            }
+        })
+    }
+
+    /// Add a sub-directory, keeping what is already known of it if it exists. Fails if there is a
+    /// file with that name.
+    pub fn add_dir(&mut self, name: &str) -> anyhow::Result<&mut Dir> {
+        let node = self
+            .children
+            .entry(name.to_owned())
+            .or_insert_with(|| Node::Dir(Dir::new(name)));
+        match node {
+            Node::Dir(dir) => Ok(dir),
+            Node::File(_) => bail!("{name} is already listed as a file"),
+        }
+    }
+
+    /// Add a file, replacing the file with the same name if any. Fails if there is a directory
+    /// with that name.
+    pub fn add_file(&mut self, name: &str, size: u64) -> anyhow::Result<()> {
+        if let Some(Node::Dir(_)) = self.children.get(name) {
+            bail!("{name} is already listed as a directory");
+        }
+        self.children.insert(
+            name.to_owned(),
+            Node::File(File {
+                name: name.to_owned(),
+                size,
+            }),
+        );
+        Ok(())
+    }
+
+    /// Every directory and file, this one first, depth first, children sorted by name.
+    ///
+    /// This directory is taken as the root of the tree for the paths.
+    pub fn entries(&self) -> Vec<Entry> {
+        let mut entries = vec![];
+        self.collect_entries("", 0, &mut entries);
+        entries
+    }
+
+    /// Push the entries of this directory, returning its size
+    fn collect_entries(&self, path: &str, depth: usize, entries: &mut Vec<Entry>) -> u64 {
+        let index = entries.len();
+        entries.push(Entry {
+            path: if path.is_empty() { "/" } else { path }.to_owned(),
+            depth,
+            size: 0,
+            is_dir: true,
+        });
+
+        let mut size = 0;
+        for child in self.children.values() {
+            let child_path = format!("{path}/{}", child.name());
+            size += match child {
+                Node::Dir(dir) => dir.collect_entries(&child_path, depth + 1, entries),
+                Node::File(file) => {
+                    entries.push(Entry {
+                        path: child_path,
+                        depth: depth + 1,
+                        size: file.size,
+                        is_dir: false,
+                    });
+                    file.size
+                }
+            };
+        }
+        entries[index].size = size;
+        size
+    }
+
+    /// Every directory with its total size
+    pub fn dirs(&self) -> impl Iterator<Item = Entry> {
+        self.entries().into_iter().filter(|e| e.is_dir)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::Dir;
+
+    #[test]
+    pub fn test_tree() {
+        let mut root = Dir::new("/");
+        root.add_dir("a").unwrap().add_file("b", 10).unwrap();
+        root.add_file("c", 5).unwrap();
+        // Adding an existing directory doesn't empty it
+        let d = root.add_dir("a").unwrap().add_dir("d").unwrap();
+        d.add_file("e", 1).unwrap();
+
+        // A file and a directory never replace each other
+        assert!(root.add_dir("c").is_err());
+        assert!(root.add_file("a", 1).is_err());
+
+        assert_eq!(root.size(), 16);
+        assert_eq!(root.dir(&["a", "d"]).unwrap().size(), 1);
+        assert!(root.dir(&["c"]).is_none());
+
+        let entries = root
+            .entries()
+            .into_iter()
+            .map(|e| (e.path, e.depth, e.size))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            vec![
+                ("/".to_owned(), 0, 16),
+                ("/a".to_owned(), 1, 11),
+                ("/a/b".to_owned(), 2, 10),
+                ("/a/d".to_owned(), 2, 1),
+                ("/a/d/e".to_owned(), 3, 1),
+                ("/c".to_owned(), 1, 5),
+            ]
+        );
+    }
+}
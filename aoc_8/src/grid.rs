@@ -0,0 +1,92 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+
+/// Heights of the trees, row by row
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    heights: Vec<u8>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize, heights: Vec<u8>) -> Self {
+        assert_eq!(width * height, heights.len());
+        Grid {
+            width,
+            height,
+            heights,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Height of the tree at column `x` and row `y`
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.heights[y * self.width + x]
+    }
+
+    pub fn row(&self, y: usize) -> &[u8] {
+        &self.heights[y * self.width..(y + 1) * self.width]
+    }
+}
+
+/// One row per line, one digit per tree. Every row must be as wide as the first one.
+impl FromStr for Grid {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut width = None;
+        let mut heights = vec![];
+        let mut height = 0;
+        for (y, line) in s.lines().enumerate() {
+            let line = line.trim_end();
+            for (x, c) in line.chars().enumerate() {
+                let tree = c.to_digit(10).with_context(|| {
+                    format!("Row {}, column {}: {c:?} is not a height", y + 1, x + 1)
+                })?;
+                heights.push(tree as u8);
+            }
+
+            let row_width = line.chars().count();
+            match width {
+                None => width = Some(row_width),
+                Some(width) if width != row_width => {
+                    bail!("Row {} is {row_width} trees wide, expected {width}", y + 1)
+                }
+                Some(_) => {}
+            }
+            height += 1;
+        }
+
+        Ok(Grid::new(width.unwrap_or_default(), height, heights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Grid;
+
+    #[test]
+    pub fn test_parse() {
+        let grid = "123\n456\n".parse::<Grid>().unwrap();
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.get(2, 1), 6);
+        assert_eq!(grid.row(0), &[1, 2, 3]);
+
+        let error = "30373\n255\n65332".parse::<Grid>().unwrap_err();
+        assert_eq!(error.to_string(), "Row 2 is 3 trees wide, expected 5");
+        let error = "303\n2x5".parse::<Grid>().unwrap_err();
+        assert_eq!(error.to_string(), "Row 2, column 2: 'x' is not a height");
+
+        let grid = "".parse::<Grid>().unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+}
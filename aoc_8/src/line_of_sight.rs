@@ -0,0 +1,198 @@
+use anyhow::bail;
+
+use crate::Grid;
+
+/// Someone standing above the tree at `x`, `y`, their eyes at `height`
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Observer {
+    pub x: usize,
+    pub y: usize,
+    pub height: f64,
+}
+
+impl Observer {
+    /// Whether the top of the tree at `x`, `y` can be seen.
+    ///
+    /// Every tree fills its square up to its height. The line from the eyes, in the middle of the
+    /// observer's square, to the middle of the tree top must pass strictly above every tree in
+    /// between. A line going exactly through the corner of a square doesn't touch its tree.
+    pub fn sees(&self, grid: &Grid, x: usize, y: usize) -> anyhow::Result<bool> {
+        self.check(grid)?;
+        check_position(grid, x, y)?;
+        Ok(self.line_is_clear(grid, x, y))
+    }
+
+    /// `sees`, once both ends of the line are known to be in the grid
+    fn line_is_clear(&self, grid: &Grid, x: usize, y: usize) -> bool {
+        let target = grid.get(x, y) as f64;
+        let (dx, dy) = (x as i64 - self.x as i64, y as i64 - self.y as i64);
+        let (steps_x, steps_y) = (dx.unsigned_abs(), dy.unsigned_abs());
+        let (sign_x, sign_y) = (dx.signum(), dy.signum());
+        // The line crosses the column borders at t = (2i + 1) / (2 |dx|) and the row borders at
+        // t = (2j + 1) / (2 |dy|), compared without dividing
+        let (mut i, mut j) = (0, 0);
+        let (mut cx, mut cy) = (self.x as i64, self.y as i64);
+        let mut entry = 0.0;
+        let line_height = |t: f64| self.height + t * (target - self.height);
+        let crossing = |n: u64, steps: u64| (2 * n + 1) as f64 / (2 * steps) as f64;
+
+        while i < steps_x || j < steps_y {
+            // Which border comes first, both at once on a corner
+            let (next_x, next_y) = match (i < steps_x, j < steps_y) {
+                (true, true) => {
+                    let a = (2 * i + 1) * steps_y;
+                    let b = (2 * j + 1) * steps_x;
+                    (a <= b, b <= a)
+                }
+                (x, y) => (x, y),
+            };
+            let exit = if next_x {
+                crossing(i, steps_x)
+            } else {
+                crossing(j, steps_y)
+            };
+
+            // Leaving the current square, which is not checked for the observer's own one
+            if (cx, cy) != (self.x as i64, self.y as i64) {
+                let tree = grid.get(cx as usize, cy as usize) as f64;
+                if tree >= line_height(entry).min(line_height(exit)) {
+                    return false;
+                }
+            }
+
+            if next_x {
+                cx += sign_x;
+                i += 1;
+            }
+            if next_y {
+                cy += sign_y;
+                j += 1;
+            }
+            entry = exit;
+        }
+        true
+    }
+
+    /// Coordinates of every tree the observer can see, row by row
+    pub fn visible_trees(&self, grid: &Grid) -> anyhow::Result<Vec<(usize, usize)>> {
+        self.check(grid)?;
+        let mut trees = vec![];
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                if (x, y) != (self.x, self.y) && self.line_is_clear(grid, x, y) {
+                    trees.push((x, y));
+                }
+            }
+        }
+        Ok(trees)
+    }
+
+    /// The grid with the trees the observer can see, `.` for the hidden ones and `@` for the
+    /// observer
+    pub fn overlay(&self, grid: &Grid) -> anyhow::Result<String> {
+        self.check(grid)?;
+        let mut overlay = String::new();
+        for y in 0..grid.height() {
+            for x in 0..grid.width() {
+                overlay.push(if (x, y) == (self.x, self.y) {
+                    '@'
+                } else if self.line_is_clear(grid, x, y) {
+                    char::from(b'0' + grid.get(x, y))
+                } else {
+                    '.'
+                });
+            }
+            overlay.push('\n');
+        }
+        Ok(overlay)
+    }
+
+    fn check(&self, grid: &Grid) -> anyhow::Result<()> {
+        check_position(grid, self.x, self.y)
+    }
+}
+
+fn check_position(grid: &Grid, x: usize, y: usize) -> anyhow::Result<()> {
+    if x >= grid.width() || y >= grid.height() {
+        bail!(
+            "({x}, {y}) is outside of the {}x{} grid",
+            grid.width(),
+            grid.height()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::line_of_sight::Observer;
+    use crate::Grid;
+
+    #[test]
+    pub fn test_line_of_sight() {
+        let grid = "11511".parse::<Grid>().unwrap();
+        let low = Observer {
+            x: 0,
+            y: 0,
+            height: 2.0,
+        };
+        assert_eq!(low.visible_trees(&grid).unwrap(), vec![(1, 0), (2, 0)]);
+        // High enough to see over the 5, but not the tree right behind it
+        let high = Observer {
+            height: 20.0,
+            ..low
+        };
+        assert_eq!(
+            high.visible_trees(&grid).unwrap(),
+            vec![(1, 0), (2, 0), (4, 0)]
+        );
+
+        // Through corners, only the middle tree is in the way. Looking level over a tree of the same
+        // height doesn't work, hence the extra half.
+        let grid = "111\n191\n111".parse::<Grid>().unwrap();
+        let observer = Observer {
+            x: 0,
+            y: 0,
+            height: 1.5,
+        };
+        assert_eq!(
+            observer.overlay(&grid).unwrap(),
+            "@11\n19.\n1.."
+                .lines()
+                .map(|line| format!("{line}\n"))
+                .collect::<String>()
+        );
+
+        let outside = Observer { x: 3, ..observer };
+        assert_eq!(
+            outside.visible_trees(&grid).unwrap_err().to_string(),
+            "(3, 0) is outside of the 3x3 grid"
+        );
+    }
+
+    #[test]
+    pub fn test_straight_lines() {
+        // From the top of a tree, looking along a row: the shorter trees in between never block
+        // the view of a taller one
+        let grid = "5137\n0000".parse::<Grid>().unwrap();
+        let observer = Observer {
+            x: 0,
+            y: 0,
+            height: 5.0,
+        };
+        assert!(observer.sees(&grid, 3, 0).unwrap());
+        assert!(observer.sees(&grid, 1, 1).unwrap());
+        assert!(!Observer {
+            height: 0.5,
+            ..observer
+        }
+        .sees(&grid, 3, 1)
+        .unwrap());
+
+        assert_eq!(
+            observer.sees(&grid, 4, 0).unwrap_err().to_string(),
+            "(4, 0) is outside of the 4x2 grid"
+        );
+        assert!(Observer { y: 2, ..observer }.sees(&grid, 0, 0).is_err());
+    }
+}
@@ -1,11 +1,96 @@
-use aoc_8::{part1, part2};
+use anyhow::Context;
+use aoc_8::{parse_input, part1, part2, Observer, TreeMap};
 
 fn main() {
-    if let Some(x) = part1("input.txt") {
-        println!("Result part 1: {x}");
+    let args = std::env::args()
+        .skip(1)
+        .filter(|arg| arg != "--diagonals")
+        .collect::<Vec<String>>();
+    let diagonals = std::env::args().any(|arg| arg == "--diagonals");
+    let result = match args.first().map(String::as_str) {
+        Some("csv") => tree_map(diagonals).map(|map| print!("{}", map.to_csv())),
+        Some("overlay") => tree_map(diagonals).map(|map| print!("{}", map.overlay())),
+        Some("best") => best(diagonals),
+        Some("heatmap") => heatmap(args.get(1).map_or("heatmap.ppm", String::as_str), diagonals),
+        Some("summary") => tree_map(diagonals).map(|map| {
+            let summary = map.summary();
+            println!("Visible trees: {}", summary.visible);
+            println!("Best scenic score: {}", summary.best_scenic_score);
+        }),
+        Some("observer") => observer(&args[1..]),
+        _ => {
+            solve();
+            Ok(())
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("{e:#}");
     }
+}
+
+fn solve() {
+    match part1("input.txt") {
+        Ok(x) => println!("Result part 1: {x}"),
+        Err(e) => eprintln!("{e:#}"),
+    }
+
+    match part2("input.txt") {
+        Ok(x) => println!("Result part 2: {x}"),
+        Err(e) => eprintln!("{e:#}"),
+    }
+}
 
-    if let Some(x) = part2("input.txt") {
-        println!("Result part 2: {x}");
+fn tree_map(diagonals: bool) -> anyhow::Result<TreeMap> {
+    let grid = parse_input("input.txt")?;
+    Ok(if diagonals {
+        TreeMap::with_diagonals(&grid)
+    } else {
+        TreeMap::new(&grid)
+    })
+}
+
+fn best(diagonals: bool) -> anyhow::Result<()> {
+    let map = tree_map(diagonals)?;
+    for (x, y) in map.best_trees() {
+        let view = map.get(x, y);
+        let distances = map
+            .directions()
+            .iter()
+            .map(|direction| {
+                let distance = view.distance(*direction).unwrap_or_default();
+                format!("{} {distance}", direction.name())
+            })
+            .collect::<Vec<String>>();
+        println!(
+            "({x}, {y}), height {}: {}, scenic score {}",
+            view.height,
+            distances.join(", "),
+            view.scenic_score()
+        );
     }
+    Ok(())
+}
+
+fn heatmap(path: &str, diagonals: bool) -> anyhow::Result<()> {
+    std::fs::write(path, tree_map(diagonals)?.heatmap())?;
+    println!("Heatmap written to {path}");
+    Ok(())
+}
+
+/// `observer X Y HEIGHT`
+fn observer(args: &[String]) -> anyhow::Result<()> {
+    let [x, y, height] = args else {
+        anyhow::bail!("Usage: observer X Y HEIGHT");
+    };
+    let observer = Observer {
+        x: x.parse().with_context(|| format!("Bad column {x:?}"))?,
+        y: y.parse().with_context(|| format!("Bad row {y:?}"))?,
+        height: height
+            .parse()
+            .with_context(|| format!("Bad height {height:?}"))?,
+    };
+    let grid = parse_input("input.txt")?;
+    print!("{}", observer.overlay(&grid)?);
+    println!("Visible trees: {}", observer.visible_trees(&grid)?.len());
+    Ok(())
 }
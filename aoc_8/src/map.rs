@@ -0,0 +1,368 @@
+use std::fmt::Write;
+
+use crate::visibility::Sight;
+use crate::{Grid, Summary};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl Direction {
+    /// The directions of the puzzle
+    pub const CARDINAL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+    pub const ALL: [Direction; 8] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+        Direction::UpLeft,
+        Direction::UpRight,
+        Direction::DownLeft,
+        Direction::DownRight,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+            Direction::UpLeft => "up_left",
+            Direction::UpRight => "up_right",
+            Direction::DownLeft => "down_left",
+            Direction::DownRight => "down_right",
+        }
+    }
+
+    /// Move by one tree in this direction, `y` going down
+    pub fn step(&self) -> (isize, isize) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+            Direction::Right => (1, 0),
+            Direction::UpLeft => (-1, -1),
+            Direction::UpRight => (1, -1),
+            Direction::DownLeft => (-1, 1),
+            Direction::DownRight => (1, 1),
+        }
+    }
+}
+
+/// Everything a tree sees, indexed by `Direction`. The directions the map doesn't look at are
+/// left out.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq)]
+pub struct TreeView {
+    pub height: u8,
+    /// Whether the tree can be seen from the edge in this direction
+    pub visible_from: [bool; 8],
+    /// Number of trees seen looking in this direction
+    pub distances: [Option<u32>; 8],
+}
+
+impl TreeView {
+    pub fn is_visible(&self) -> bool {
+        self.visible_from.contains(&true)
+    }
+
+    pub fn is_visible_from(&self, direction: Direction) -> bool {
+        self.visible_from[direction as usize]
+    }
+
+    pub fn distance(&self, direction: Direction) -> Option<u32> {
+        self.distances[direction as usize]
+    }
+
+    pub fn scenic_score(&self) -> u64 {
+        self.distances.iter().flatten().map(|d| *d as u64).product()
+    }
+}
+
+/// The view of every tree of the grid. Takes much more memory than `summary`.
+#[derive(Debug, Clone)]
+pub struct TreeMap {
+    width: usize,
+    height: usize,
+    directions: &'static [Direction],
+    views: Vec<TreeView>,
+}
+
+impl TreeMap {
+    /// Look along the rows and columns, like the puzzle
+    pub fn new(grid: &Grid) -> Self {
+        Self::looking(grid, &Direction::CARDINAL)
+    }
+
+    /// Look along the diagonals too
+    pub fn with_diagonals(grid: &Grid) -> Self {
+        Self::looking(grid, &Direction::ALL)
+    }
+
+    fn looking(grid: &Grid, directions: &'static [Direction]) -> Self {
+        let (width, height) = (grid.width(), grid.height());
+        let mut map = TreeMap {
+            width,
+            height,
+            directions,
+            views: vec![TreeView::default(); width * height],
+        };
+
+        for y in 0..height {
+            for x in 0..width {
+                map.views[y * width + x].height = grid.get(x, y);
+            }
+        }
+        // Each line is walked from the edge its trees are looking at, so it starts with the trees
+        // having nothing in front of them
+        for &direction in directions {
+            let (dx, dy) = direction.step();
+            for y in 0..height {
+                for x in 0..width {
+                    if map.neighbour(x, y, dx, dy).is_none() {
+                        map.look_along(x, y, direction);
+                    }
+                }
+            }
+        }
+        map
+    }
+
+    fn neighbour(&self, x: usize, y: usize, dx: isize, dy: isize) -> Option<(usize, usize)> {
+        let x = x.checked_add_signed(dx).filter(|x| *x < self.width)?;
+        let y = y.checked_add_signed(dy).filter(|y| *y < self.height)?;
+        Some((x, y))
+    }
+
+    /// Walk back from the edge tree at `x`, `y`, every tree looking in `direction`
+    fn look_along(&mut self, x: usize, y: usize, direction: Direction) {
+        let (dx, dy) = direction.step();
+        let mut sight = Sight::default();
+        let mut tree = Some((x, y));
+        let mut position = 0;
+        while let Some((x, y)) = tree {
+            let view = &mut self.views[y * self.width + x];
+            let (visible, distance) = sight.look(view.height, position);
+            view.visible_from[direction as usize] = visible;
+            view.distances[direction as usize] = Some(distance as u32);
+            tree = self.neighbour(x, y, -dx, -dy);
+            position += 1;
+        }
+    }
+
+    pub fn directions(&self) -> &[Direction] {
+        self.directions
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> &TreeView {
+        &self.views[y * self.width + x]
+    }
+
+    /// Every tree with its coordinates, row by row
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize), &TreeView)> {
+        self.views
+            .iter()
+            .enumerate()
+            .map(|(index, view)| ((index % self.width, index / self.width), view))
+    }
+
+    pub fn best_scenic_score(&self) -> u64 {
+        self.views
+            .iter()
+            .map(TreeView::scenic_score)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Coordinates of the trees with the best scenic score
+    pub fn best_trees(&self) -> Vec<(usize, usize)> {
+        let best = self.best_scenic_score();
+        self.iter()
+            .filter(|(_, view)| view.scenic_score() == best)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    pub fn summary(&self) -> Summary {
+        Summary {
+            visible: self.views.iter().filter(|view| view.is_visible()).count(),
+            best_scenic_score: self.best_scenic_score(),
+        }
+    }
+
+    /// One line per tree, with a header
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("x,y,height");
+        for direction in self.directions {
+            write!(csv, ",visible_from_{}", direction.name()).unwrap();
+        }
+        for direction in self.directions {
+            write!(csv, ",distance_{}", direction.name()).unwrap();
+        }
+        csv.push_str(",scenic_score\n");
+
+        for ((x, y), view) in self.iter() {
+            write!(csv, "{x},{y},{}", view.height).unwrap();
+            for direction in self.directions {
+                write!(csv, ",{}", view.is_visible_from(*direction) as u8).unwrap();
+            }
+            for direction in self.directions {
+                let distance = view.distance(*direction).unwrap_or_default();
+                write!(csv, ",{distance}").unwrap();
+            }
+            writeln!(csv, ",{}", view.scenic_score()).unwrap();
+        }
+        csv
+    }
+
+    /// The grid with the visible trees only, `.` for the hidden ones and `*` for the best spots,
+    /// if any tree has a view at all
+    pub fn overlay(&self) -> String {
+        let best = self.best_scenic_score();
+        let mut overlay = String::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let view = self.get(x, y);
+                overlay.push(if best > 0 && view.scenic_score() == best {
+                    '*'
+                } else if view.is_visible() {
+                    char::from(b'0' + view.height)
+                } else {
+                    '.'
+                });
+            }
+            overlay.push('\n');
+        }
+        overlay
+    }
+
+    /// Binary PPM image of the scenic scores, one pixel per tree, from dark blue for the worst to
+    /// red for the best
+    pub fn heatmap(&self) -> Vec<u8> {
+        let best = self.best_scenic_score().max(1) as f64;
+        let mut image = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for view in &self.views {
+            // The square root spreads the low scores, most trees have one
+            let t = (view.scenic_score() as f64 / best).sqrt();
+            let red = (255.0 * t) as u8;
+            let green = (255.0 * (1.0 - (2.0 * t - 1.0).abs())) as u8;
+            let blue = (255.0 * (1.0 - t)) as u8 / 2 + 64;
+            image.extend([red, green, blue]);
+        }
+        image
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::map::{Direction, TreeMap};
+    use crate::{parse_input, summary, Grid};
+
+    #[test]
+    pub fn test_tree_map() {
+        let grid = parse_input("input_test.txt").unwrap();
+        let map = TreeMap::new(&grid);
+
+        // The middle 5 of the fourth row
+        let view = map.get(2, 3);
+        let distances = Direction::CARDINAL.map(|direction| view.distance(direction).unwrap());
+        assert_eq!(distances, [2, 1, 2, 2]);
+        assert_eq!(view.distance(Direction::UpLeft), None);
+        assert_eq!(view.scenic_score(), 8);
+        assert!(!view.is_visible_from(Direction::Up));
+        assert!(view.is_visible_from(Direction::Left));
+        // The top left 5 is only visible from the top and the left
+        assert_eq!(map.get(1, 1).visible_from[..4], [true, false, true, false]);
+
+        assert_eq!(map.best_trees(), vec![(2, 3)]);
+        assert_eq!(map.summary(), summary(&grid));
+    }
+
+    /// Walk from the tree in the given direction until something blocks the view
+    fn naive(grid: &Grid, x: usize, y: usize, direction: Direction) -> (bool, u32) {
+        let (dx, dy) = direction.step();
+        let (mut cx, mut cy, mut distance) = (x as isize + dx, y as isize + dy, 0);
+        while (0..grid.width() as isize).contains(&cx) && (0..grid.height() as isize).contains(&cy)
+        {
+            distance += 1;
+            if grid.get(cx as usize, cy as usize) >= grid.get(x, y) {
+                return (false, distance);
+            }
+            cx += dx;
+            cy += dy;
+        }
+        (true, distance)
+    }
+
+    #[test]
+    pub fn test_diagonals() {
+        let grid = parse_input("input_test.txt").unwrap();
+        let map = TreeMap::with_diagonals(&grid);
+        for ((x, y), view) in map.iter() {
+            for direction in Direction::ALL {
+                let (visible, distance) = naive(&grid, x, y, direction);
+                assert_eq!(
+                    view.is_visible_from(direction),
+                    visible,
+                    "{x},{y} {direction:?}"
+                );
+                assert_eq!(
+                    view.distance(direction),
+                    Some(distance),
+                    "{x},{y} {direction:?}"
+                );
+            }
+        }
+
+        // The center 3 is still hidden, the 5 and the 3 in the corners are in the way
+        let view = map.get(2, 2);
+        assert!(!view.is_visible());
+        assert_eq!(view.distance(Direction::UpRight), Some(2));
+        // The 4 next to the 9 now sees the edge over the 2
+        assert!(map.get(3, 3).is_visible_from(Direction::UpRight));
+        assert_eq!(map.summary().visible, 22);
+    }
+
+    #[test]
+    pub fn test_exports() {
+        let map = TreeMap::new(&parse_input("input_test.txt").unwrap());
+
+        assert_eq!(map.overlay(), "30373\n255.2\n65.32\n3.*.9\n35390\n");
+        // Every tree is on the edge, none has a view
+        let flat = TreeMap::new(&"12\n34".parse::<Grid>().unwrap());
+        assert_eq!(flat.overlay(), "12\n34\n");
+
+        let csv = map.to_csv();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "x,y,height,visible_from_up,visible_from_down,visible_from_left,visible_from_right,\
+             distance_up,distance_down,distance_left,distance_right,scenic_score"
+        );
+        assert_eq!(lines.nth(17).unwrap(), "2,3,5,0,1,1,0,2,1,2,2,8");
+
+        let heatmap = map.heatmap();
+        assert!(heatmap.starts_with(b"P6\n5 5\n255\n"));
+        assert_eq!(heatmap.len(), b"P6\n5 5\n255\n".len() + 5 * 5 * 3);
+    }
+}
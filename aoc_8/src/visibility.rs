@@ -0,0 +1,157 @@
+use crate::Grid;
+
+/// What we know looking back along a line of trees, from the edge to the current tree
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Sight {
+    tallest: Option<u8>,
+    /// Trees that can still block the view, strictly decreasing heights, with their position
+    blockers: Vec<(u8, usize)>,
+}
+
+impl Sight {
+    /// Look back to the edge from the tree of the given height, `position` trees away from it.
+    ///
+    /// Returns whether the tree is visible from the edge, and how many trees it sees.
+    pub(crate) fn look(&mut self, height: u8, position: usize) -> (bool, usize) {
+        let visible = self.tallest.is_none_or(|tallest| height > tallest);
+        self.tallest = self.tallest.max(Some(height));
+
+        // Trees smaller than this one can't block anybody behind it anymore, and the same goes for
+        // a tree of the same height
+        while self.blockers.last().is_some_and(|(h, _)| *h < height) {
+            self.blockers.pop();
+        }
+        let distance = match self.blockers.last() {
+            Some((_, blocker)) => position - blocker,
+            None => position,
+        };
+        if self.blockers.last().is_some_and(|(h, _)| *h == height) {
+            self.blockers.pop();
+        }
+        self.blockers.push((height, position));
+
+        (visible, distance)
+    }
+}
+
+/// Both answers of the puzzle
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Summary {
+    /// Number of trees visible from outside the grid
+    pub visible: usize,
+    pub best_scenic_score: u64,
+}
+
+/// Look at every tree in every direction, in O(width × height).
+///
+/// The first pass goes down the rows looking up each column. The second one goes up the rows
+/// looking down each column, and then along the row once every column is known.
+pub fn summary(grid: &Grid) -> Summary {
+    let (width, height) = (grid.width(), grid.height());
+    // Viewing distance looking up, the only thing kept for every tree with the visibility
+    let mut up_distances = vec![0u32; width * height];
+    let mut visible = vec![false; width * height];
+
+    let mut columns = vec![Sight::default(); width];
+    for y in 0..height {
+        for (x, sight) in columns.iter_mut().enumerate() {
+            let (seen, distance) = sight.look(grid.get(x, y), y);
+            visible[y * width + x] = seen;
+            up_distances[y * width + x] = distance as u32;
+        }
+    }
+
+    let mut summary = Summary {
+        visible: 0,
+        best_scenic_score: 0,
+    };
+    let mut columns = vec![Sight::default(); width];
+    let mut vertical = vec![0u64; width];
+    let mut left_distances = vec![0u64; width];
+    for y in (0..height).rev() {
+        let row = grid.row(y);
+        let index = y * width;
+        for (x, sight) in columns.iter_mut().enumerate() {
+            let (seen, distance) = sight.look(row[x], height - 1 - y);
+            visible[index + x] |= seen;
+            vertical[x] = up_distances[index + x] as u64 * distance as u64;
+        }
+
+        let mut left = Sight::default();
+        for x in 0..width {
+            let (seen, distance) = left.look(row[x], x);
+            visible[index + x] |= seen;
+            left_distances[x] = distance as u64;
+        }
+        let mut right = Sight::default();
+        for x in (0..width).rev() {
+            let (seen, distance) = right.look(row[x], width - 1 - x);
+            if visible[index + x] || seen {
+                summary.visible += 1;
+            }
+            let score = vertical[x] * left_distances[x] * distance as u64;
+            summary.best_scenic_score = summary.best_scenic_score.max(score);
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visibility::summary;
+    use crate::Grid;
+
+    /// Look from every tree, one at a time
+    fn naive(grid: &Grid) -> (usize, u64) {
+        let (width, height) = (grid.width() as i64, grid.height() as i64);
+        let mut visible = 0;
+        let mut best = 0;
+        for y in 0..height {
+            for x in 0..width {
+                let tree = grid.get(x as usize, y as usize);
+                let mut seen = false;
+                let mut score = 1;
+                for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+                    let (mut cx, mut cy, mut distance) = (x + dx, y + dy, 0);
+                    let mut blocked = false;
+                    while (0..width).contains(&cx) && (0..height).contains(&cy) {
+                        distance += 1;
+                        if grid.get(cx as usize, cy as usize) >= tree {
+                            blocked = true;
+                            break;
+                        }
+                        cx += dx;
+                        cy += dy;
+                    }
+                    seen |= !blocked;
+                    score *= distance;
+                }
+                visible += seen as usize;
+                best = best.max(score);
+            }
+        }
+        (visible, best)
+    }
+
+    #[test]
+    pub fn test_against_naive() {
+        let mut seed = 12345u64;
+        for (width, height) in [(5, 5), (1, 7), (7, 1), (13, 4), (30, 30)] {
+            let heights = (0..width * height)
+                .map(|_| {
+                    seed ^= seed << 13;
+                    seed ^= seed >> 7;
+                    seed ^= seed << 17;
+                    (seed % 10) as u8
+                })
+                .collect();
+            let grid = Grid::new(width, height, heights);
+            let summary = summary(&grid);
+            assert_eq!(
+                (summary.visible, summary.best_scenic_score),
+                naive(&grid),
+                "{width}x{height}"
+            );
+        }
+    }
+}
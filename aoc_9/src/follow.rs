@@ -0,0 +1,103 @@
+use crate::{Point, Position};
+
+/// How a knot moves once the knot in front of it, its leader, has moved
+pub trait FollowRule<P = Position> {
+    /// Where the knot goes, knowing where its leader was before moving and where it is now
+    fn follow(&self, knot: P, leader_before: P, leader: P) -> P;
+}
+
+/// The rule of the puzzle: once the leader isn't touching anymore, even diagonally, move by one
+/// toward it, diagonally if needed
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Chebyshev;
+
+impl<P: Point> FollowRule<P> for Chebyshev {
+    fn follow(&self, knot: P, _: P, leader: P) -> P {
+        let distance = distance(leader, knot);
+        if is_detached(&distance) {
+            // Move only by at most 1
+            knot.offset(distance.map(i32::signum))
+        } else {
+            knot
+        }
+    }
+}
+
+/// Like `Chebyshev`, but never moving diagonally: the knot goes along the axis where the leader
+/// is the furthest, the first one on a tie, `x` then `y` then `z`. It can fall behind when the
+/// leader moves diagonally.
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Manhattan;
+
+impl<P: Point> FollowRule<P> for Manhattan {
+    fn follow(&self, knot: P, _: P, leader: P) -> P {
+        let distance = distance(leader, knot);
+        if !is_detached(&distance) {
+            return knot;
+        }
+        let mut axis = 0;
+        for (i, d) in distance.iter().enumerate() {
+            if d.abs() > distance[axis].abs() {
+                axis = i;
+            }
+        }
+        let mut offset = [0; 3];
+        offset[axis] = distance[axis].signum();
+        knot.offset(offset)
+    }
+}
+
+/// Like a snake: once the leader isn't touching anymore, take its previous place
+#[derive(Debug, Default, Copy, Clone)]
+pub struct Snap;
+
+impl<P: Point> FollowRule<P> for Snap {
+    fn follow(&self, knot: P, leader_before: P, leader: P) -> P {
+        if is_detached(&distance(leader, knot)) {
+            leader_before
+        } else {
+            knot
+        }
+    }
+}
+
+/// Compute the distance between two knots, on each axis
+fn distance<P: Point>(head: P, tail: P) -> [i32; 3] {
+    let (head, tail) = (head.coords(), tail.coords());
+    [head[0] - tail[0], head[1] - tail[1], head[2] - tail[2]]
+}
+
+/// Taking a distance as an argument, determine if the knot is "detached" from the next one
+fn is_detached(distance: &[i32; 3]) -> bool {
+    distance.iter().any(|d| d.abs() > 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::follow::{Chebyshev, FollowRule, Manhattan, Snap};
+
+    #[test]
+    pub fn test_rules() {
+        // The leader just went up and right, away from the knot
+        let (knot, before, leader) = ((0, 0), (1, 1), (2, 1));
+        assert_eq!(Chebyshev.follow(knot, before, leader), (1, 1));
+        assert_eq!(Manhattan.follow(knot, before, leader), (1, 0));
+        assert_eq!(Snap.follow(knot, before, leader), (1, 1));
+
+        // Still touching, nobody moves
+        for rule in [&Chebyshev as &dyn FollowRule, &Manhattan, &Snap] {
+            assert_eq!(rule.follow((0, 0), (0, 0), (1, 1)), (0, 0));
+        }
+
+        // In space, the knot steps along the three axes at once
+        let (knot, before, leader) = ((0, 0, 0), (1, 1, 1), (1, 1, 2));
+        assert_eq!(Chebyshev.follow(knot, before, leader), (1, 1, 1));
+        assert_eq!(Manhattan.follow(knot, before, leader), (0, 0, 1));
+
+        // A diagonal move of the leader
+        let (knot, before, leader) = ((0, 0), (1, 0), (2, -1));
+        assert_eq!(Chebyshev.follow(knot, before, leader), (1, -1));
+        assert_eq!(Manhattan.follow(knot, before, leader), (1, 0));
+        assert_eq!(Snap.follow(knot, before, leader), (1, 0));
+    }
+}
@@ -1,108 +1,380 @@
 use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
-#[derive(Debug)]
-enum Direction {
+use anyhow::{bail, Context};
+
+pub use crate::follow::{Chebyshev, FollowRule, Manhattan, Snap};
+pub use crate::point::{Point, Position, Position3};
+pub use crate::render::{render_frame, render_visited, Viewport};
+
+mod follow;
+mod point;
+mod render;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Direction {
     Up,
     Down,
     Right,
     Left,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+    Forward,
+    Backward,
+    /// Any other of the 26 neighbours in space, see `from_step`
+    Diagonal(Offset),
+}
+
+/// A move by one along at least two axes, only built by `Direction::from_step` so that it is
+/// never one of the named directions
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Offset([i32; 3]);
+
+impl Offset {
+    pub fn get(&self) -> [i32; 3] {
+        self.0
+    }
+}
+
+impl Direction {
+    /// How the head moves, `y` going up and `z` going forward
+    pub fn step(&self) -> [i32; 3] {
+        match self {
+            Direction::Up => [0, 1, 0],
+            Direction::Down => [0, -1, 0],
+            Direction::Right => [1, 0, 0],
+            Direction::Left => [-1, 0, 0],
+            Direction::UpLeft => [-1, 1, 0],
+            Direction::UpRight => [1, 1, 0],
+            Direction::DownLeft => [-1, -1, 0],
+            Direction::DownRight => [1, -1, 0],
+            Direction::Forward => [0, 0, 1],
+            Direction::Backward => [0, 0, -1],
+            Direction::Diagonal(offset) => offset.get(),
+        }
+    }
+
+    /// The direction moving by `step`, each coordinate being -1, 0 or 1
+    pub fn from_step(step: [i32; 3]) -> Option<Self> {
+        let named = [
+            Direction::Up,
+            Direction::Down,
+            Direction::Right,
+            Direction::Left,
+            Direction::UpLeft,
+            Direction::UpRight,
+            Direction::DownLeft,
+            Direction::DownRight,
+            Direction::Forward,
+            Direction::Backward,
+        ];
+        if step == [0; 3] || step.iter().any(|d| d.abs() > 1) {
+            None
+        } else {
+            let direction = named.into_iter().find(|d| d.step() == step);
+            Some(direction.unwrap_or(Direction::Diagonal(Offset(step))))
+        }
+    }
 }
 
-#[derive(Debug)]
-struct RopeMove {
-    direction: Direction,
-    count: i32,
+/// `U` or `D`, then `L` or `R`, then `F` or `B`
+impl Display for Direction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let [x, y, z] = self.step();
+        for (d, letters) in [
+            (y, ["D", "", "U"]),
+            (x, ["L", "", "R"]),
+            (z, ["B", "", "F"]),
+        ] {
+            write!(f, "{}", letters[(d + 1) as usize])?;
+        }
+        Ok(())
+    }
 }
-#[derive(Debug)]
-struct Rope {
-    knots: Vec<(i32, i32)>,
-    visited_by_tails: HashSet<(i32, i32)>,
+
+/// Any mix of `U` or `D`, `L` or `R`, and `F` or `B`, each axis at most once
+impl FromStr for Direction {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut step = [0; 3];
+        let mut seen = [false; 3];
+        for letter in s.chars() {
+            let (axis, d) = match letter {
+                'R' => (0, 1),
+                'L' => (0, -1),
+                'U' => (1, 1),
+                'D' => (1, -1),
+                'F' => (2, 1),
+                'B' => (2, -1),
+                _ => bail!("{s:?} is not a direction"),
+            };
+            if seen[axis] {
+                bail!("{s:?} moves twice along the same axis");
+            }
+            seen[axis] = true;
+            step[axis] = d;
+        }
+        Direction::from_step(step).with_context(|| format!("{s:?} is not a direction"))
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct RopeMove {
+    pub direction: Direction,
+    pub count: i32,
+}
+
+impl Display for RopeMove {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.direction, self.count)
+    }
+}
+
+/// A rope on a plane, or in space with `Position3` knots. On a plane, moving forward or backward
+/// is an error.
+#[derive(Debug, Clone)]
+pub struct Rope<R = Chebyshev, P = Position> {
+    knots: Vec<P>,
+    /// Positions of each knot since the start
+    visited_by_knots: Vec<HashSet<P>>,
+    rule: R,
 }
 
 impl Rope {
-    fn new(size: usize) -> Self {
-        let mut visited_by_tails = HashSet::default();
-        visited_by_tails.insert((0, 0));
-        let knots = vec![(0, 0); size]; // We keep the head
+    /// A rope following the rule of the puzzle
+    pub fn new(size: usize) -> anyhow::Result<Self> {
+        Rope::with_rule(size, Chebyshev)
+    }
+}
 
-        Rope {
-            knots,
-            visited_by_tails,
+impl<R: FollowRule> Rope<R> {
+    pub fn with_rule(size: usize, rule: R) -> anyhow::Result<Self> {
+        Rope::build(size, rule)
+    }
+}
+
+impl Rope<Chebyshev, Position3> {
+    /// A rope in space following the rule of the puzzle
+    pub fn new_3d(size: usize) -> anyhow::Result<Self> {
+        Rope::with_rule_3d(size, Chebyshev)
+    }
+}
+
+impl<R: FollowRule<Position3>> Rope<R, Position3> {
+    pub fn with_rule_3d(size: usize, rule: R) -> anyhow::Result<Self> {
+        Rope::build(size, rule)
+    }
+}
+
+impl<R: FollowRule<P>, P: Point> Rope<R, P> {
+    /// A rope of `size` knots, the head included, so at least one
+    fn build(size: usize, rule: R) -> anyhow::Result<Self> {
+        if size == 0 {
+            bail!("A rope needs at least one knot");
         }
+        let knots = vec![P::default(); size]; // We keep the head
+        let visited_by_knots = vec![HashSet::from([P::default()]); size];
+
+        Ok(Rope {
+            knots,
+            visited_by_knots,
+            rule,
+        })
     }
 
-    pub fn move_rope(&mut self, direction: &RopeMove) {
+    /// Apply a whole move, or nothing if the rope can't go that way
+    pub fn move_rope(&mut self, direction: &RopeMove) -> anyhow::Result<()> {
+        Self::check(direction.direction)?;
         for _ in 0..direction.count {
-            let mut head = self.knots.first_mut().unwrap(); // Move the head first
-            match direction.direction {
-                Direction::Up => head.1 += 1,
-                Direction::Down => head.1 -= 1,
-                Direction::Right => head.0 += 1,
-                Direction::Left => head.0 -= 1,
-            }
+            self.advance(direction.direction);
+        }
+        Ok(())
+    }
 
-            for i in 1..self.knots.len() {
-                let next_knot = self.knots[i - 1];
-                let mut current_knot = self.knots.get_mut(i).unwrap();
+    /// Move the head by one, and let the other knots follow
+    pub fn step(&mut self, direction: Direction) -> anyhow::Result<()> {
+        Self::check(direction)?;
+        self.advance(direction);
+        Ok(())
+    }
+
+    fn check(direction: Direction) -> anyhow::Result<()> {
+        if !P::reaches(direction.step()) {
+            bail!("A rope on a plane can't move {direction}");
+        }
+        Ok(())
+    }
+
+    /// `step`, once the direction is known to be fine
+    fn advance(&mut self, direction: Direction) {
+        let head = self.knots.first_mut().unwrap(); // Move the head first
+        let mut leader_before = *head;
+        *head = head.offset(direction.step());
+        self.visited_by_knots[0].insert(*head);
 
-                let distance = distance(&next_knot, current_knot);
-                if is_detached(&distance) {
-                    current_knot.0 += distance.0.signum(); // Move only by at most 1
-                    current_knot.1 += distance.1.signum();
+        for i in 1..self.knots.len() {
+            let knot = self.knots[i];
+            self.knots[i] = self.rule.follow(knot, leader_before, self.knots[i - 1]);
+            self.visited_by_knots[i].insert(self.knots[i]);
+            leader_before = knot;
+        }
+    }
+
+    /// Positions of the knots, the head first
+    pub fn knots(&self) -> &[P] {
+        &self.knots
+    }
+
+    /// Positions the tail has been to, the start included
+    pub fn visited(&self) -> &HashSet<P> {
+        self.visited_by_knots.last().unwrap()
+    }
+
+    /// Positions a knot has been to, the start included. The head is the knot 0.
+    pub fn visited_by(&self, knot: usize) -> Option<&HashSet<P>> {
+        self.visited_by_knots.get(knot)
+    }
+
+    /// Follow the moves one step at a time, once they are all known to be fine
+    pub fn steps(self, moves: &[RopeMove]) -> anyhow::Result<Steps<'_, R, P>> {
+        for (index, rope_move) in moves.iter().enumerate() {
+            Self::check(rope_move.direction)
+                .with_context(|| format!("Move {}: {rope_move}", index + 1))?;
+        }
+        Ok(Steps {
+            rope: self,
+            moves: moves.iter().enumerate(),
+            current: None,
+        })
+    }
+}
+
+/// The rope right after the head moved by one
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Step<P = Position> {
+    /// Index of the move in the input
+    pub move_index: usize,
+    /// How many steps of that move are done, from 1 to its count
+    pub step: i32,
+    pub knots: Vec<P>,
+}
+
+/// Iterator over every single step of the rope
+#[derive(Debug, Clone)]
+pub struct Steps<'a, R = Chebyshev, P = Position> {
+    rope: Rope<R, P>,
+    moves: std::iter::Enumerate<std::slice::Iter<'a, RopeMove>>,
+    /// The move being done, and how many steps of it are done
+    current: Option<(usize, &'a RopeMove, i32)>,
+}
+
+impl<R, P> Steps<'_, R, P> {
+    /// The rope as it is after the last step
+    pub fn rope(&self) -> &Rope<R, P> {
+        &self.rope
+    }
+}
+
+impl<R: FollowRule<P>, P: Point> Iterator for Steps<'_, R, P> {
+    type Item = Step<P>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.current {
+                Some((move_index, rope_move, done)) if done < rope_move.count => {
+                    self.rope.advance(rope_move.direction);
+                    self.current = Some((move_index, rope_move, done + 1));
+                    return Some(Step {
+                        move_index,
+                        step: done + 1,
+                        knots: self.rope.knots.clone(),
+                    });
+                }
+                _ => {
+                    let (move_index, rope_move) = self.moves.next()?;
+                    self.current = Some((move_index, rope_move, 0));
                 }
             }
-            self.visited_by_tails.insert(*self.knots.last().unwrap());
         }
     }
 }
 
-/// Compute the distance between two knots
-fn distance((head_x, head_y): &(i32, i32), (tail_x, tail_y): &(i32, i32)) -> (i32, i32) {
-    ((head_x - tail_x), (head_y - tail_y))
+pub fn solution(input_path: &str, rope_size: usize) -> Option<usize> {
+    solution_with_rule(input_path, rope_size, Chebyshev)
 }
 
-/// Taking a distance as an argument, determine if the knot is "detached" from the next one
-fn is_detached((hx, hy): &(i32, i32)) -> bool {
-    hx.abs() > 1 || hy.abs() > 1
+/// Number of positions the tail visited, with another way for the knots to follow
+pub fn solution_with_rule<R: FollowRule>(
+    input_path: &str,
+    rope_size: usize,
+    rule: R,
+) -> Option<usize> {
+    let mut rope = Rope::with_rule(rope_size, rule).ok()?;
+    read_moves(input_path)
+        .ok()?
+        .iter()
+        .try_for_each(|x| rope.move_rope(x))
+        .ok()?;
+    Some(rope.visited().len())
 }
 
-pub fn solution(input_path: &str, rope_size: usize) -> Option<usize> {
-    let mut rope = Rope::new(rope_size);
-    std::fs::read_to_string(input_path)
+/// Number of positions the tail visited, the rope moving in space
+pub fn solution_3d(input_path: &str, rope_size: usize) -> Option<usize> {
+    let mut rope = Rope::new_3d(rope_size).ok()?;
+    read_moves(input_path)
         .ok()?
+        .iter()
+        .try_for_each(|x| rope.move_rope(x))
+        .ok()?;
+    Some(rope.visited().len())
+}
+
+/// Read the moves of the head, one per line
+pub fn read_moves(input_path: &str) -> anyhow::Result<Vec<RopeMove>> {
+    let input =
+        std::fs::read_to_string(input_path).with_context(|| format!("Can't read {input_path}"))?;
+    parse_moves(&input)
+}
+
+/// Parse the moves of the head, one per line
+pub fn parse_moves(input: &str) -> anyhow::Result<Vec<RopeMove>> {
+    input
         .lines()
-        .filter_map(|x| x.split_once(' '))
-        .map(parse_direction)
-        .for_each(|x| rope.move_rope(&x));
-    Some(rope.visited_by_tails.len())
-}
-
-fn parse_direction((x, y): (&str, &str)) -> RopeMove {
-    let count = y.parse::<i32>().unwrap();
-    match x {
-        "U" => RopeMove {
-            direction: Direction::Up,
-            count,
-        },
-        "D" => RopeMove {
-            direction: Direction::Down,
-            count,
-        },
-        "R" => RopeMove {
-            direction: Direction::Right,
-            count,
-        },
-        "L" => RopeMove {
-            direction: Direction::Left,
-            count,
-        },
-        _ => unreachable!(),
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| {
+            line.split_once(' ')
+                .context("Expected a direction and a count")
+                .and_then(parse_direction)
+                .with_context(|| format!("Line {}: {line:?}", number + 1))
+        })
+        .collect()
+}
+
+fn parse_direction((x, y): (&str, &str)) -> anyhow::Result<RopeMove> {
+    let count = y
+        .trim()
+        .parse::<i32>()
+        .with_context(|| format!("{y:?} is not a count"))?;
+    if count < 0 {
+        bail!("The count can't be negative, found {count}");
     }
+    Ok(RopeMove {
+        direction: x.parse()?,
+        count,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::solution;
+    use crate::{
+        parse_moves, read_moves, solution, solution_3d, solution_with_rule, Direction, Manhattan,
+        Rope, RopeMove, Snap,
+    };
 
     #[test]
     pub fn test_part1() {
@@ -118,4 +390,108 @@ mod tests {
     pub fn test_part2() {
         assert_eq!(solution("input_test_part2.txt", 10), Some(36))
     }
+
+    #[test]
+    pub fn test_steps() {
+        let moves = read_moves("input_test_part1.txt").unwrap();
+        let mut steps = Rope::new(2).unwrap().steps(&moves).unwrap();
+
+        // R 4: the tail starts following on the second step
+        let first = steps.next().unwrap();
+        assert_eq!((first.move_index, first.step), (0, 1));
+        assert_eq!(first.knots, vec![(1, 0), (0, 0)]);
+        assert_eq!(steps.next().unwrap().knots, vec![(2, 0), (1, 0)]);
+
+        let last = steps.by_ref().last().unwrap();
+        assert_eq!((last.move_index, last.step), (7, 2));
+        assert_eq!(last.knots, vec![(2, 2), (1, 2)]);
+        assert_eq!(steps.rope().visited().len(), 13);
+
+        // Every knot but the tail went further
+        let rope = steps.rope();
+        assert_eq!(rope.visited_by(1), Some(rope.visited()));
+        assert!(rope.visited_by(0).unwrap().len() > 13);
+        assert_eq!(rope.visited_by(2), None);
+
+        let error = parse_moves("R 4\nU4").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 2: \"U4\": Expected a direction and a count"
+        );
+        let error = parse_moves("L -2").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 1: \"L -2\": The count can't be negative, found -2"
+        );
+        assert!(Rope::new(0).is_err());
+        assert_eq!(solution("input_test_part1.txt", 0), None);
+    }
+
+    #[test]
+    pub fn test_rules_and_diagonal_moves() {
+        let up_right = RopeMove {
+            direction: Direction::UpRight,
+            count: 3,
+        };
+        assert_eq!(up_right.to_string(), "UR 3");
+        let mut rope = Rope::new(2).unwrap();
+        rope.move_rope(&up_right).unwrap();
+        assert_eq!(rope.knots(), &[(3, 3), (2, 2)]);
+        assert_eq!(rope.visited().len(), 3);
+
+        // Without diagonal steps, the tail can't keep up
+        let mut rope = Rope::with_rule(2, Manhattan).unwrap();
+        rope.move_rope(&up_right).unwrap();
+        assert_eq!(rope.knots(), &[(3, 3), (1, 1)]);
+
+        let mut rope = Rope::with_rule(3, Snap).unwrap();
+        for (direction, count) in [(Direction::Right, 3), (Direction::Up, 1)] {
+            rope.move_rope(&RopeMove { direction, count }).unwrap();
+        }
+        assert_eq!(rope.knots(), &[(3, 1), (2, 0), (1, 0)]);
+
+        // With two knots, taking the place of the head is what the tail does anyway
+        assert_eq!(
+            solution_with_rule("input_test_part1.txt", 2, Snap),
+            Some(13)
+        );
+    }
+
+    #[test]
+    pub fn test_3d() {
+        // A flat input gives the same answers in space
+        assert_eq!(solution_3d("input_test_part1.txt", 2), Some(13));
+        assert_eq!(solution_3d("input_test_part2.txt", 10), Some(36));
+
+        let moves = parse_moves("F 3\nURF 1\nB 1").unwrap();
+        assert_eq!(moves[1].direction.step(), [1, 1, 1]);
+        assert_eq!(moves[1].to_string(), "URF 1");
+        // A diagonal is never one of the named directions, nor staying in place
+        assert_eq!(Direction::from_step([1, 0, 0]), Some(Direction::Right));
+        assert_eq!(Direction::from_step([0; 3]), None);
+        assert_eq!(Direction::from_step([2, 0, 1]), None);
+        let mut rope = Rope::new_3d(2).unwrap();
+        moves[..2].iter().for_each(|x| rope.move_rope(x).unwrap());
+        assert_eq!(rope.knots(), &[(1, 1, 4), (1, 1, 3)]);
+        assert_eq!(rope.visited().len(), 4);
+
+        // On a plane, moving forward or backward is an error, and the rope stays where it was
+        let mut rope = Rope::new(2).unwrap();
+        let error = rope.move_rope(&moves[1]).unwrap_err();
+        assert_eq!(error.to_string(), "A rope on a plane can't move URF");
+        assert_eq!(rope.knots(), &[(0, 0), (0, 0)]);
+        assert!(rope.step(Direction::Backward).is_err());
+        let error = Rope::new(2).unwrap().steps(&moves).unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Move 1: F 3: A rope on a plane can't move F"
+        );
+
+        assert_eq!("LU".parse::<Direction>().unwrap(), Direction::UpLeft);
+        let error = parse_moves("UD 1").unwrap_err();
+        assert_eq!(
+            format!("{error:#}"),
+            "Line 1: \"UD 1\": \"UD\" moves twice along the same axis"
+        );
+    }
 }
@@ -1,6 +1,31 @@
-use aoc_9::solution;
+use anyhow::{bail, Context};
+use aoc_9::{
+    read_moves, render_frame, render_visited, solution, solution_3d, Chebyshev, FollowRule,
+    Manhattan, Rope, Snap, Viewport,
+};
 
 fn main() {
+    let args = std::env::args().collect::<Vec<String>>();
+    if args.get(1).map(String::as_str) == Some("render") {
+        if let Err(e) = render(&args[2..]) {
+            eprintln!("{e:#}");
+        }
+        return;
+    }
+    if args.get(1).map(String::as_str) == Some("3d") {
+        // `3d [INPUT] [KNOTS]`, the input may move forward and backward with `F` and `B`
+        let input = args.get(2).map_or("input.txt", String::as_str);
+        let knots = args
+            .get(3)
+            .and_then(|knots| knots.parse().ok())
+            .unwrap_or(10);
+        match solution_3d(input, knots) {
+            Some(x) => println!("Positions visited by the tail: {x}"),
+            None => eprintln!("Can't move a rope of {knots} knots with the moves in {input}"),
+        }
+        return;
+    }
+
     if let Some(x) = solution("input.txt", 1) {
         println!("Part 1: {x}");
     }
@@ -9,3 +34,78 @@ fn main() {
         println!("Part 1: {x}");
     }
 }
+
+/// Options of the `render` mode
+struct Options {
+    input: String,
+    knots: usize,
+    every_step: bool,
+    /// Whose visited positions to draw, the tail by default
+    visited_by: Option<usize>,
+}
+
+/// `render [INPUT] [KNOTS] [--steps] [--rule=chebyshev|manhattan|snap] [--knot=N]`: draw the rope
+/// after every move, or every step, and then where a knot has been
+fn render(args: &[String]) -> anyhow::Result<()> {
+    let mut options = Options {
+        input: "input.txt".to_owned(),
+        knots: 10,
+        every_step: false,
+        visited_by: None,
+    };
+    let mut rule = "chebyshev";
+    let mut positional = 0;
+    for arg in args {
+        if arg == "--steps" {
+            options.every_step = true;
+        } else if let Some(name) = arg.strip_prefix("--rule=") {
+            rule = name;
+        } else if let Some(knot) = arg.strip_prefix("--knot=") {
+            options.visited_by = Some(knot.parse().with_context(|| format!("Bad knot {knot:?}"))?);
+        } else {
+            match positional {
+                0 => options.input = arg.clone(),
+                1 => options.knots = arg.parse().with_context(|| format!("Bad size {arg:?}"))?,
+                _ => bail!("Unexpected argument {arg:?}"),
+            }
+            positional += 1;
+        }
+    }
+
+    match rule {
+        "chebyshev" => render_with(&options, Chebyshev),
+        "manhattan" => render_with(&options, Manhattan),
+        "snap" => render_with(&options, Snap),
+        _ => bail!("Unknown rule {rule:?}, expected chebyshev, manhattan or snap"),
+    }
+}
+
+fn render_with<R: FollowRule>(options: &Options, rule: R) -> anyhow::Result<()> {
+    let moves = read_moves(&options.input)?;
+    let mut steps = Rope::with_rule(options.knots, rule)?.steps(&moves)?;
+    let frames = steps.by_ref().collect::<Vec<_>>();
+    let viewport = Viewport::fit(frames.iter().flat_map(|step| step.knots.clone()));
+
+    println!("== Initial State ==\n");
+    println!("{}", render_frame(&viewport, &vec![(0, 0); options.knots]));
+    for step in &frames {
+        let rope_move = &moves[step.move_index];
+        if step.step == 1 {
+            println!("== {rope_move} ==\n");
+        }
+        if options.every_step || step.step == rope_move.count {
+            println!("{}", render_frame(&viewport, &step.knots));
+        }
+    }
+
+    let knot = options
+        .visited_by
+        .unwrap_or(options.knots.saturating_sub(1));
+    let visited = steps
+        .rope()
+        .visited_by(knot)
+        .with_context(|| format!("The rope has no knot {knot}"))?;
+    println!("== Visited by knot {knot} ==\n");
+    print!("{}", render_visited(&viewport, visited));
+    Ok(())
+}
@@ -0,0 +1,55 @@
+use std::fmt::Debug;
+use std::hash::Hash;
+
+/// Column and row of a knot, `y` going up
+pub type Position = (i32, i32);
+
+/// A knot in space, `z` going forward
+pub type Position3 = (i32, i32, i32);
+
+/// Where a knot can be. Everything is computed in three dimensions, a plane being the space where
+/// `z` is always 0.
+pub trait Point: Copy + Eq + Hash + Debug + Default {
+    fn coords(self) -> [i32; 3];
+
+    /// The point closest to the coordinates, on the plane the coordinates outside of it are
+    /// dropped
+    fn from_coords(coords: [i32; 3]) -> Self;
+
+    /// Whether moving by the offset stays in the space of the point, a plane has no `z`
+    fn reaches(offset: [i32; 3]) -> bool;
+
+    /// The point moved by the given offset
+    fn offset(self, offset: [i32; 3]) -> Self {
+        let [x, y, z] = self.coords();
+        Self::from_coords([x + offset[0], y + offset[1], z + offset[2]])
+    }
+}
+
+impl Point for Position {
+    fn coords(self) -> [i32; 3] {
+        [self.0, self.1, 0]
+    }
+
+    fn from_coords([x, y, _]: [i32; 3]) -> Self {
+        (x, y)
+    }
+
+    fn reaches([_, _, z]: [i32; 3]) -> bool {
+        z == 0
+    }
+}
+
+impl Point for Position3 {
+    fn coords(self) -> [i32; 3] {
+        [self.0, self.1, self.2]
+    }
+
+    fn from_coords([x, y, z]: [i32; 3]) -> Self {
+        (x, y, z)
+    }
+
+    fn reaches(_: [i32; 3]) -> bool {
+        true
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashSet;
+
+use crate::Position;
+
+/// The part of the plane to draw, bounds included
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Viewport {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl Viewport {
+    /// The smallest viewport showing the start and all the given positions
+    pub fn fit(positions: impl IntoIterator<Item = Position>) -> Self {
+        let mut viewport = Viewport {
+            min: (0, 0),
+            max: (0, 0),
+        };
+        for (x, y) in positions {
+            viewport.min = (viewport.min.0.min(x), viewport.min.1.min(y));
+            viewport.max = (viewport.max.0.max(x), viewport.max.1.max(y));
+        }
+        viewport
+    }
+
+    /// Draw every cell, the top row first
+    fn draw(&self, cell: impl Fn(Position) -> char) -> String {
+        let mut drawing = String::new();
+        for y in (self.min.1..=self.max.1).rev() {
+            for x in self.min.0..=self.max.0 {
+                drawing.push(cell((x, y)));
+            }
+            drawing.push('\n');
+        }
+        drawing
+    }
+}
+
+/// Name of a knot in the puzzle: `H` for the head, then `1` to `9`, `T` for the tail of a rope of
+/// two knots. Knots after the ninth one are drawn as `+`, the tail of such a rope as `T`.
+fn label(index: usize, size: usize) -> char {
+    match index {
+        0 => 'H',
+        _ if size == 2 || (size > 10 && index == size - 1) => 'T',
+        1..=9 => char::from(b'0' + index as u8),
+        _ => '+',
+    }
+}
+
+/// The rope like in the puzzle, a knot hiding the ones behind it, and `s` for the start
+pub fn render_frame(viewport: &Viewport, knots: &[Position]) -> String {
+    viewport.draw(
+        |position| match knots.iter().position(|knot| *knot == position) {
+            Some(index) => label(index, knots.len()),
+            None if position == (0, 0) => 's',
+            None => '.',
+        },
+    )
+}
+
+/// Where the tail has been, `s` for the start
+pub fn render_visited(viewport: &Viewport, visited: &HashSet<Position>) -> String {
+    viewport.draw(|position| {
+        if position == (0, 0) {
+            's'
+        } else if visited.contains(&position) {
+            '#'
+        } else {
+            '.'
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::render::{render_frame, render_visited, Viewport};
+    use crate::{read_moves, Rope};
+
+    #[test]
+    pub fn test_render() {
+        let moves = read_moves("input_test_part1.txt").unwrap();
+        let steps = Rope::new(2)
+            .unwrap()
+            .steps(&moves)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let viewport = Viewport::fit(steps.iter().flat_map(|step| step.knots.clone()));
+        assert_eq!(
+            viewport,
+            Viewport {
+                min: (0, 0),
+                max: (5, 4)
+            }
+        );
+
+        // After U 4
+        assert_eq!(
+            render_frame(&viewport, &steps[7].knots),
+            "....H.\n....T.\n......\n......\ns.....\n"
+        );
+        let mut rope = Rope::new(2).unwrap();
+        moves.iter().for_each(|x| rope.move_rope(x).unwrap());
+        assert_eq!(
+            render_visited(&viewport, rope.visited()),
+            "..##..\n...##.\n.####.\n....#.\ns###..\n"
+        );
+    }
+
+    #[test]
+    pub fn test_render_long_rope() {
+        let moves = read_moves("input_test_part2.txt").unwrap();
+        let steps = Rope::new(10)
+            .unwrap()
+            .steps(&moves)
+            .unwrap()
+            .collect::<Vec<_>>();
+        let viewport = Viewport {
+            min: (-11, -5),
+            max: (14, 15),
+        };
+
+        // After R 5, the knots 5 to 9 cover the start
+        let frame = render_frame(&viewport, &steps[4].knots);
+        assert_eq!(frame.lines().nth(15), Some("...........54321H........."));
+        assert!(!frame.contains('s'));
+        // After U 8
+        let frame = render_frame(&viewport, &steps[12].knots);
+        assert_eq!(frame.lines().nth(7), Some("................H........."));
+        assert_eq!(frame.lines().nth(11), Some("...............54........."));
+        assert_eq!(frame.lines().nth(15), Some("...........9.............."));
+    }
+}
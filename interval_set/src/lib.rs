@@ -0,0 +1,375 @@
+use std::fmt::Debug;
+use std::iter::once;
+use std::ops::RangeInclusive;
+
+/// Integer types that can be used as bounds of an `IntervalSet`
+pub trait Bound: Copy + Ord + Debug {
+    /// The next value, if there is one
+    fn successor(self) -> Option<Self>;
+
+    /// The previous value, if there is one
+    fn predecessor(self) -> Option<Self>;
+
+    /// Number of values from `start` to `end`, both included. A u128 so that the whole range of
+    /// a u64 fits.
+    fn count(start: Self, end: Self) -> u128;
+}
+
+macro_rules! impl_bound {
+    ($($t:ty),*) => {
+        $(
+            impl Bound for $t {
+                fn successor(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn predecessor(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                fn count(start: Self, end: Self) -> u128 {
+                    (end as i128 - start as i128 + 1) as u128
+                }
+            }
+        )*
+    };
+}
+
+impl_bound!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A set of integers, stored as a sorted list of inclusive ranges.
+///
+/// The ranges are always kept disjoint and non adjacent: inserting `1..=3` then `4..=6` leaves a
+/// single `1..=6` range.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<RangeInclusive<T>>,
+}
+
+impl<T> Default for IntervalSet<T> {
+    fn default() -> Self {
+        IntervalSet { ranges: vec![] }
+    }
+}
+
+impl<T: Bound> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a range to the set, merging it with the ranges it overlaps or touches
+    pub fn insert(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = range.into_inner();
+
+        // Ranges ending before `start - 1` and starting after `end + 1` are left untouched, every
+        // range in between is merged in the new one.
+        let first = self
+            .ranges
+            .partition_point(|r| r.end().successor().is_some_and(|e| e < start));
+        let last = self
+            .ranges
+            .partition_point(|r| end.successor().is_none_or(|e| *r.start() <= e));
+
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, once(start..=end));
+    }
+
+    /// Remove a range from the set, splitting the ranges it cuts
+    pub fn remove(&mut self, range: RangeInclusive<T>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = range.into_inner();
+
+        let first = self.ranges.partition_point(|r| *r.end() < start);
+        let last = self.ranges.partition_point(|r| *r.start() <= end);
+        if first >= last {
+            return;
+        }
+
+        let mut remainders = Vec::with_capacity(2);
+        let left = &self.ranges[first];
+        if *left.start() < start {
+            // `start` is not the minimum, as something is before it
+            remainders.push(*left.start()..=start.predecessor().unwrap());
+        }
+        let right = &self.ranges[last - 1];
+        if *right.end() > end {
+            remainders.push(end.successor().unwrap()..=*right.end());
+        }
+        self.ranges.splice(first..last, remainders);
+    }
+
+    /// Add every range of `other` to this set
+    pub fn merge(&mut self, other: &IntervalSet<T>) {
+        for range in &other.ranges {
+            self.insert(range.clone());
+        }
+    }
+
+    pub fn union(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut union = self.clone();
+        union.merge(other);
+        union
+    }
+
+    pub fn intersection(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut ranges = vec![];
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = *a.start().max(b.start());
+            let end = *a.end().min(b.end());
+            if start <= end {
+                ranges.push(start..=end);
+            }
+            // Move forward on the range ending first, it can't intersect anything else
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        IntervalSet { ranges }
+    }
+
+    pub fn difference(&self, other: &IntervalSet<T>) -> IntervalSet<T> {
+        let mut difference = self.clone();
+        for range in &other.ranges {
+            difference.remove(range.clone());
+        }
+        difference
+    }
+
+    /// Every value of `bounds` that is not in the set
+    pub fn complement(&self, bounds: RangeInclusive<T>) -> IntervalSet<T> {
+        IntervalSet::from(bounds).difference(self)
+    }
+
+    /// Number of values in the set
+    pub fn covered_len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|r| T::count(*r.start(), *r.end()))
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The disjoint ranges of the set, sorted
+    pub fn ranges(&self) -> impl Iterator<Item = &RangeInclusive<T>> {
+        self.ranges.iter()
+    }
+
+    /// The holes between the ranges of the set, sorted
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<T>> + '_ {
+        self.ranges.windows(2).map(|pair| {
+            // Ranges are not adjacent, so there is always at least a value between them
+            pair[0].end().successor().unwrap()..=pair[1].start().predecessor().unwrap()
+        })
+    }
+
+    pub fn contains(&self, value: &T) -> bool {
+        let index = self.ranges.partition_point(|r| r.end() < value);
+        self.ranges.get(index).is_some_and(|r| r.start() <= value)
+    }
+
+    /// Check that every value of `range` is in the set
+    pub fn contains_range(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return true;
+        }
+        let index = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(index)
+            .is_some_and(|r| r.start() <= range.start() && range.end() <= r.end())
+    }
+
+    /// Check that at least one value of `range` is in the set
+    pub fn overlaps(&self, range: &RangeInclusive<T>) -> bool {
+        if range.is_empty() {
+            return false;
+        }
+        let index = self.ranges.partition_point(|r| r.end() < range.start());
+        self.ranges
+            .get(index)
+            .is_some_and(|r| r.start() <= range.end())
+    }
+}
+
+impl<T: Bound> From<RangeInclusive<T>> for IntervalSet<T> {
+    fn from(range: RangeInclusive<T>) -> Self {
+        let mut set = IntervalSet::new();
+        set.insert(range);
+        set
+    }
+}
+
+impl<T: Bound> FromIterator<RangeInclusive<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<T>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<T: Bound> Extend<RangeInclusive<T>> for IntervalSet<T> {
+    fn extend<I: IntoIterator<Item = RangeInclusive<T>>>(&mut self, iter: I) {
+        for range in iter {
+            self.insert(range);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+    use std::ops::RangeInclusive;
+
+    use proptest::prelude::*;
+
+    use crate::IntervalSet;
+
+    #[test]
+    pub fn test_insert_merges() {
+        let set = IntervalSet::from_iter([1..=3, 4..=6, 10..=12, 8..=8]);
+        assert_eq!(
+            set.ranges().cloned().collect::<Vec<_>>(),
+            vec![1..=6, 8..=8, 10..=12]
+        );
+        assert_eq!(set.covered_len(), 10);
+        assert_eq!(set.gaps().collect::<Vec<_>>(), vec![7..=7, 9..=9]);
+
+        let set = IntervalSet::from_iter([0..=u8::MAX, 3..=5]);
+        assert_eq!(set.covered_len(), 256);
+
+        let set = IntervalSet::from_iter([0..=u64::MAX]);
+        assert_eq!(set.covered_len(), u64::MAX as u128 + 1);
+        let set = IntervalSet::from_iter([i64::MIN..=i64::MAX]);
+        assert_eq!(set.covered_len(), 1 << 64);
+    }
+
+    #[test]
+    pub fn test_set_operations() {
+        let a = IntervalSet::from_iter([0..=10, 20..=30]);
+        let b = IntervalSet::from_iter([5..=25]);
+
+        let intersection = a.intersection(&b);
+        assert_eq!(intersection, IntervalSet::from_iter([5..=10, 20..=25]));
+        assert_eq!(a.union(&b), IntervalSet::from(0..=30));
+        assert_eq!(a.difference(&b), IntervalSet::from_iter([0..=4, 26..=30]));
+        assert_eq!(
+            a.complement(-5..=35),
+            IntervalSet::from_iter([-5..=-1, 11..=19, 31..=35])
+        );
+
+        assert!(a.contains(&20));
+        assert!(!a.contains(&15));
+        assert!(a.contains_range(&(2..=8)));
+        assert!(!a.contains_range(&(8..=22)));
+        assert!(a.overlaps(&(8..=22)));
+        assert!(!a.overlaps(&(11..=19)));
+    }
+
+    fn model(ranges: &[(i16, i16)]) -> BTreeSet<i16> {
+        ranges.iter().flat_map(|(a, b)| *a..=*b).collect()
+    }
+
+    fn set(ranges: &[(i16, i16)]) -> IntervalSet<i16> {
+        ranges.iter().map(|(a, b)| *a..=*b).collect()
+    }
+
+    fn to_model(set: &IntervalSet<i16>) -> BTreeSet<i16> {
+        set.ranges().cloned().flatten().collect()
+    }
+
+    fn is_normalized(set: &IntervalSet<i16>) -> bool {
+        let ranges = set.ranges().collect::<Vec<&RangeInclusive<i16>>>();
+        ranges.iter().all(|r| !r.is_empty())
+            && ranges.windows(2).all(|w| *w[0].end() + 1 < *w[1].start())
+    }
+
+    /// Up to 8 ranges of at most 12 values, some of them empty
+    fn ranges_strategy() -> impl Strategy<Value = Vec<(i16, i16)>> {
+        prop::collection::vec(
+            (-50i16..50, 0i16..15).prop_map(|(a, l)| (a, a + l - 3)),
+            0..8,
+        )
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_model(a in ranges_strategy(), b in ranges_strategy()) {
+            let (set_a, set_b) = (set(&a), set(&b));
+            let (model_a, model_b) = (model(&a), model(&b));
+
+            prop_assert!(is_normalized(&set_a));
+            prop_assert_eq!(to_model(&set_a), model_a.clone());
+            prop_assert_eq!(set_a.covered_len(), model_a.len() as u128);
+
+            let union = set_a.union(&set_b);
+            prop_assert!(is_normalized(&union));
+            prop_assert_eq!(to_model(&union), model_a.union(&model_b).copied().collect());
+
+            let intersection = set_a.intersection(&set_b);
+            prop_assert!(is_normalized(&intersection));
+            prop_assert_eq!(
+                to_model(&intersection),
+                model_a.intersection(&model_b).copied().collect()
+            );
+
+            let difference = set_a.difference(&set_b);
+            prop_assert!(is_normalized(&difference));
+            prop_assert_eq!(
+                to_model(&difference),
+                model_a.difference(&model_b).copied().collect()
+            );
+
+            let complement = set_a.complement(-20..=20);
+            prop_assert_eq!(
+                to_model(&complement),
+                (-20..=20).filter(|x| !model_a.contains(x)).collect()
+            );
+
+            for value in -60..70 {
+                prop_assert_eq!(set_a.contains(&value), model_a.contains(&value));
+            }
+        }
+
+        #[test]
+        fn prop_range_queries(a in ranges_strategy(), start in -60i16..60, len in 0i16..20) {
+            let (set_a, model_a) = (set(&a), model(&a));
+            let range = start..=start + len;
+
+            prop_assert_eq!(
+                set_a.contains_range(&range),
+                range.clone().all(|x| model_a.contains(&x))
+            );
+            prop_assert_eq!(
+                set_a.overlaps(&range),
+                range.clone().any(|x| model_a.contains(&x))
+            );
+        }
+
+        #[test]
+        fn prop_gaps_fill_the_holes(a in ranges_strategy()) {
+            let set_a = set(&a);
+            let gaps = set_a.gaps().collect::<IntervalSet<i16>>();
+            prop_assert!(gaps.intersection(&set_a).is_empty());
+
+            let first = set_a.ranges().next().map(|r| *r.start());
+            let last = set_a.ranges().last().map(|r| *r.end());
+            if let (Some(first), Some(last)) = (first, last) {
+                prop_assert_eq!(gaps.union(&set_a), IntervalSet::from(first..=last));
+            }
+        }
+    }
+}